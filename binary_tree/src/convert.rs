@@ -0,0 +1,210 @@
+use std::convert::Infallible;
+use std::error::Error;
+use std::fmt;
+
+use handson_1::Tree;
+
+use crate::TreeNode;
+
+/// The error returned when a key does not fit the key type of the other representation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyConversionError {
+    /// A negative `i32` value cannot be a `u32` key.
+    Negative(i32),
+    /// A `u32` key larger than `i32::MAX` cannot be a value.
+    TooLarge(u32),
+}
+
+impl fmt::Display for KeyConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyConversionError::Negative(value) => {
+                write!(f, "Value {value} is negative and cannot be a u32 key")
+            }
+            KeyConversionError::TooLarge(key) => {
+                write!(f, "Key {key} is larger than the largest i32 value")
+            }
+        }
+    }
+}
+
+impl Error for KeyConversionError {}
+
+impl TreeNode {
+    /// Builds the boxed tree with the same shape and keys as `tree`,
+    /// or `None` if `tree` is empty, as accepted by [`crate::count_nodes`].
+    pub fn from_tree(tree: &Tree<i32>) -> Option<Box<TreeNode>> {
+        let Ok(root) = Self::convert_tree(tree, |&key| Ok::<_, Infallible>(key));
+        root
+    }
+
+    /// Same as [`TreeNode::from_tree`] for a tree with `u32` keys, which fails
+    /// if a key is larger than `i32::MAX`.
+    pub fn try_from_tree(tree: &Tree<u32>) -> Result<Option<Box<TreeNode>>, KeyConversionError> {
+        Self::convert_tree(tree, |&key| {
+            i32::try_from(key).map_err(|_| KeyConversionError::TooLarge(key))
+        })
+    }
+
+    /// A private function that builds the boxed tree with the same shape as
+    /// `tree`, converting each key with `convert`. Children are built before
+    /// their parents, so deep trees do not overflow the stack.
+    fn convert_tree<K, E, F>(tree: &Tree<K>, convert: F) -> Result<Option<Box<TreeNode>>, E>
+    where
+        F: Fn(&K) -> Result<i32, E>,
+    {
+        let Some(root) = tree.root().and_then(|root| tree.cursor(root)) else {
+            return Ok(None);
+        };
+
+        let mut order = Vec::with_capacity(tree.len());
+        let mut stack = vec![root];
+        while let Some(cursor) = stack.pop() {
+            order.push(cursor);
+            stack.extend(cursor.left().into_iter().chain(cursor.right()));
+        }
+
+        // The built subtrees by the id of their root
+        let size = order
+            .iter()
+            .map(|cursor| cursor.id() + 1)
+            .max()
+            .unwrap_or(0);
+        let mut built: Vec<Option<Box<TreeNode>>> = Vec::with_capacity(size);
+        built.resize_with(size, || None);
+        for cursor in order.into_iter().rev() {
+            let mut node = TreeNode::new(convert(cursor.key())?);
+            node.left = cursor.left().and_then(|left| built[left.id()].take());
+            node.right = cursor.right().and_then(|right| built[right.id()].take());
+            built[cursor.id()] = Some(Box::new(node));
+        }
+
+        Ok(built[root.id()].take())
+    }
+
+    /// A private function that builds the arena tree with the same shape as the
+    /// tree rooted at this node, converting each value with `convert`.
+    /// The ids of the nodes follow the pre-order numbering of [`TreeNode::to_dot`].
+    fn convert_node<K, E, F>(&self, convert: F) -> Result<Tree<K>, E>
+    where
+        F: Fn(i32) -> Result<K, E>,
+    {
+        let mut tree = Tree::new();
+        // Each entry holds a node and where it hangs in the arena tree
+        let mut stack = vec![(self, None)];
+
+        while let Some((node, parent)) = stack.pop() {
+            let key = convert(node.value)?;
+            let id = match parent {
+                Some((parent_id, is_left)) => tree.add_node(parent_id, key, is_left),
+                None => tree.add_root(key),
+            };
+
+            if let Some(right) = &node.right {
+                stack.push((right, Some((id, false))));
+            }
+            if let Some(left) = &node.left {
+                stack.push((left, Some((id, true))));
+            }
+        }
+
+        Ok(tree)
+    }
+}
+
+/// Builds the arena tree with the same shape and keys as the tree rooted at
+/// the node. The ids of the nodes follow the pre-order numbering of [`TreeNode::to_dot`].
+impl From<&TreeNode> for Tree<i32> {
+    fn from(node: &TreeNode) -> Self {
+        let Ok(tree) = node.convert_node(Ok::<_, Infallible>);
+        tree
+    }
+}
+
+/// Same as the conversion to `Tree<i32>`, which fails if a value is negative.
+impl TryFrom<&TreeNode> for Tree<u32> {
+    type Error = KeyConversionError;
+
+    fn try_from(node: &TreeNode) -> Result<Self, Self::Error> {
+        node.convert_node(|value| {
+            u32::try_from(value).map_err(|_| KeyConversionError::Negative(value))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::count_nodes;
+
+    fn sample() -> TreeNode {
+        let mut root = TreeNode::new(12);
+
+        let mut left_child = TreeNode::new(8);
+        left_child.left = Some(Box::new(TreeNode::new(3)));
+        left_child.right = Some(Box::new(TreeNode::new(9)));
+
+        root.left = Some(Box::new(left_child));
+        root.right = Some(Box::new(TreeNode::new(20)));
+        root
+    }
+
+    #[test]
+    fn test_to_tree() {
+        let root = sample();
+        let tree = Tree::from(&root);
+
+        assert_eq!(tree.len(), 5);
+        assert_eq!(
+            tree.to_level_order(),
+            [Some(12), Some(8), Some(20), Some(3), Some(9)]
+        );
+        // Ids follow the numbering of `to_dot`
+        assert_eq!(tree.cursor(3).unwrap().key(), &9);
+
+        // The algorithms of both representations run on the same data
+        assert!(tree.is_bst());
+        assert_eq!(tree.max_path_sum(), 49);
+        assert_eq!(count_nodes(&Some(Box::new(root)), 0), (1, 52));
+    }
+
+    #[test]
+    fn test_from_tree() {
+        let tree: Tree<i32> = "[-10, 9, 20, null, null, 15, 7]".parse().unwrap();
+        let root = TreeNode::from_tree(&tree);
+
+        assert_eq!(
+            root.as_ref().unwrap().to_ascii(),
+            "-10
+|-- L: 9
+`-- R: 20
+    |-- L: 15
+    `-- R: 7
+"
+        );
+        assert_eq!(count_nodes(&root, 0), (0, 41));
+        assert!(Tree::from(root.as_deref().unwrap()).is_identical(&tree));
+        assert!(TreeNode::from_tree(&Tree::new()).is_none());
+    }
+
+    #[test]
+    fn test_u32_conversions() {
+        let tree = Tree::<u32>::try_from(&sample()).unwrap();
+        assert_eq!(tree.sum(), 52);
+        let root = TreeNode::try_from_tree(&tree).unwrap();
+        assert_eq!(root.unwrap().to_ascii(), sample().to_ascii());
+
+        let mut negative = sample();
+        negative.right = Some(Box::new(TreeNode::new(-20)));
+        assert_eq!(
+            Tree::<u32>::try_from(&negative).err(),
+            Some(KeyConversionError::Negative(-20))
+        );
+
+        let large = Tree::with_root(u32::MAX);
+        assert_eq!(
+            TreeNode::try_from_tree(&large).err(),
+            Some(KeyConversionError::TooLarge(u32::MAX))
+        );
+    }
+}
@@ -1,3 +1,9 @@
+use std::fmt::Write;
+
+mod convert;
+
+pub use convert::KeyConversionError;
+
 pub struct TreeNode {
     value: i32,
     left: Option<Box<TreeNode>>,
@@ -5,31 +11,255 @@ pub struct TreeNode {
 }
 
 impl TreeNode {
-    fn new(value: i32) -> TreeNode {
+    /// Creates a leaf node with the specified `value`.
+    pub fn new(value: i32) -> TreeNode {
         TreeNode {
             value,
             left: None,
             right: None,
         }
     }
-}
 
-pub fn count_nodes(node: &Option<Box<TreeNode>>, current_path_sum: i32) -> (i32, i32) {
-    if let Some(n) = node {
-        let left_result = count_nodes(&n.left, current_path_sum + n.value);
-        let right_result = count_nodes(&n.right, current_path_sum + n.value);
+    /// Creates a node with the specified `value` and children.
+    pub fn from_parts(
+        value: i32,
+        left: Option<Box<TreeNode>>,
+        right: Option<Box<TreeNode>>,
+    ) -> TreeNode {
+        TreeNode { value, left, right }
+    }
+
+    /// Sets the left child of the node to `left` and returns the node,
+    /// replacing the previous left subtree.
+    pub fn with_left(mut self, left: TreeNode) -> TreeNode {
+        self.left = Some(Box::new(left));
+        self
+    }
+
+    /// Sets the right child of the node to `right` and returns the node,
+    /// replacing the previous right subtree.
+    pub fn with_right(mut self, right: TreeNode) -> TreeNode {
+        self.right = Some(Box::new(right));
+        self
+    }
+
+    /// Returns the value of the node.
+    pub fn value(&self) -> i32 {
+        self.value
+    }
+
+    /// Returns the left child of the node, if any.
+    pub fn left(&self) -> Option<&TreeNode> {
+        self.left.as_deref()
+    }
+
+    /// Returns the right child of the node, if any.
+    pub fn right(&self) -> Option<&TreeNode> {
+        self.right.as_deref()
+    }
+
+    /// Renders the tree rooted at this node in the Graphviz DOT language.
+    /// Nodes are numbered in pre-order starting from 0 at this node, every node
+    /// is labelled with its number and value, and the nodes in `highlight` are filled.
+    pub fn to_dot(&self, highlight: &[usize]) -> String {
+        let mut dot = String::from("digraph Tree {\n    node [shape=circle];\n");
+
+        // Each entry holds a node, its number and the number of its parent
+        let mut stack = vec![(self, None, "")];
+        let mut next_id = 0;
+        while let Some((node, parent, port)) = stack.pop() {
+            let id = next_id;
+            next_id += 1;
+
+            let style = if highlight.contains(&id) {
+                ", style=filled, fillcolor=gold"
+            } else {
+                ""
+            };
+            writeln!(dot, "    {id} [label=\"#{id}: {}\"{style}];", node.value).unwrap();
+            if let Some(parent) = parent {
+                writeln!(dot, "    {parent} -> {id} [tailport={port}];").unwrap();
+            }
+
+            if let Some(right) = &node.right {
+                stack.push((right, Some(id), "se"));
+            }
+            if let Some(left) = &node.left {
+                stack.push((left, Some(id), "sw"));
+            }
+        }
 
-        let mut count = left_result.0 + right_result.0;
-        if left_result.1 + right_result.1 == current_path_sum {
-            count += 1;
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Renders the tree rooted at this node as indented text for the terminal,
+    /// one node per line with its side and value, e.g.
+    ///
+    /// ```text
+    /// 12
+    /// |-- L: 8
+    /// |   `-- R: 9
+    /// `-- R: 20
+    /// ```
+    pub fn to_ascii(&self) -> String {
+        let mut ascii = format!("{}\n", self.value);
+
+        // Each entry holds the prefix of its line, the node and the side it hangs on
+        let mut stack = Vec::new();
+        Self::push_children(&mut stack, "", self);
+
+        while let Some((prefix, node, side, is_last)) = stack.pop() {
+            let (branch, indent) = if is_last {
+                ("`-- ", "    ")
+            } else {
+                ("|-- ", "|   ")
+            };
+            writeln!(ascii, "{prefix}{branch}{side}: {}", node.value).unwrap();
+            Self::push_children(&mut stack, &format!("{prefix}{indent}"), node);
         }
 
-        let sum = left_result.1 + right_result.1 + n.value;
+        ascii
+    }
+
+    /// Returns the nodes of the tree rooted at this node whose descendants sum
+    /// to the same value as their ancestors, i.e. the sum of the values in the
+    /// two subtrees of the node, without the node itself, equals the sum of the
+    /// values on the path from this node down to its parent, also without the node.
+    /// A leaf below ancestors summing to zero matches, and so does the root if its
+    /// descendants sum to zero.
+    ///
+    /// Each node comes with its number in pre-order, as used by [`TreeNode::to_dot`],
+    /// so the result can be highlighted, and the nodes are sorted by number.
+    pub fn matching_nodes(&self) -> Vec<(usize, &TreeNode)> {
+        self.find_matching_nodes(0).0
+    }
+
+    /// A private function that returns the nodes matching as in
+    /// [`TreeNode::matching_nodes`] when the ancestors of this node sum to
+    /// `ancestor_sum`, together with the sum of all the values of the tree.
+    /// It uses explicit stacks, so it works on trees of any depth.
+    fn find_matching_nodes(&self, ancestor_sum: i32) -> (Vec<(usize, &TreeNode)>, i32) {
+        // The nodes in pre-order, each with the sum of its ancestors and
+        // the positions of its children in the same order
+        let mut order: Vec<(&TreeNode, i32, Option<usize>, Option<usize>)> = Vec::new();
+        let mut stack = vec![(self, ancestor_sum, None::<(usize, bool)>)];
+
+        while let Some((node, ancestor_sum, parent)) = stack.pop() {
+            let index = order.len();
+            match parent {
+                Some((parent, true)) => order[parent].2 = Some(index),
+                Some((parent, false)) => order[parent].3 = Some(index),
+                None => {}
+            }
+            order.push((node, ancestor_sum, None, None));
+
+            let path_sum = ancestor_sum + node.value;
+            if let Some(right) = &node.right {
+                stack.push((right, path_sum, Some((index, false))));
+            }
+            if let Some(left) = &node.left {
+                stack.push((left, path_sum, Some((index, true))));
+            }
+        }
+
+        // Children come after their parents, so their subtrees are summed first
+        let mut sums = vec![0; order.len()];
+        let mut matches = Vec::new();
+        for (index, &(node, ancestor_sum, left, right)) in order.iter().enumerate().rev() {
+            let descendant_sum =
+                left.map_or(0, |left| sums[left]) + right.map_or(0, |right| sums[right]);
+            if descendant_sum == ancestor_sum {
+                matches.push((index, node));
+            }
+            sums[index] = descendant_sum + node.value;
+        }
+        matches.reverse();
+
+        (matches, sums[0])
+    }
+
+    /// A private function that pushes the children of `node` on the stack used by
+    /// [`TreeNode::to_ascii`], so that they are popped left first.
+    fn push_children<'a>(
+        stack: &mut Vec<(String, &'a TreeNode, &'static str, bool)>,
+        prefix: &str,
+        node: &'a TreeNode,
+    ) {
+        let children: Vec<_> = [(&node.left, "L"), (&node.right, "R")]
+            .into_iter()
+            .filter_map(|(child, side)| child.as_deref().map(|child| (child, side)))
+            .collect();
+
+        for (i, &(child, side)) in children.iter().enumerate().rev() {
+            let is_last = i == children.len() - 1;
+            stack.push((prefix.to_string(), child, side, is_last));
+        }
+    }
+}
+
+/// Builds a [`TreeNode`] from a nested literal: `tree!(value)` is a leaf and
+/// `tree!(value, left, right)` a node with two children, where each child is
+/// `_` for a missing one, a literal for a leaf or a parenthesised subtree.
+/// Negative leaves need parentheses, e.g. `(-3)`.
+///
+/// ```
+/// use binary_tree::{count_nodes, tree};
+///
+/// let root = tree!(12, (8, 3, 9), (20, _, (-3)));
+/// assert_eq!(root.left().unwrap().right().unwrap().value(), 9);
+/// assert_eq!(count_nodes(&Some(Box::new(root)), 0).1, 49);
+/// ```
+#[macro_export]
+macro_rules! tree {
+    (@child _) => {
+        None
+    };
+    (@child ($($subtree:tt)*)) => {
+        Some(Box::new($crate::tree!($($subtree)*)))
+    };
+    (@child $leaf:literal) => {
+        Some(Box::new($crate::TreeNode::from_parts($leaf, None, None)))
+    };
+    ($value:expr) => {
+        $crate::TreeNode::from_parts($value, None, None)
+    };
+    ($value:expr, $left:tt, $right:tt) => {
+        $crate::TreeNode::from_parts(
+            $value,
+            $crate::tree!(@child $left),
+            $crate::tree!(@child $right),
+        )
+    };
+}
 
-        return (count, sum);
+/// Returns the number of nodes of the tree `node` that match as in
+/// [`TreeNode::matching_nodes`], when the ancestors of `node` sum to
+/// `current_path_sum`, and the sum of all the values of the tree.
+pub fn count_nodes(node: &Option<Box<TreeNode>>, current_path_sum: i32) -> (i32, i32) {
+    match node {
+        Some(n) => {
+            let (matches, sum) = n.find_matching_nodes(current_path_sum);
+            (matches.len() as i32, sum)
+        }
+        None => (0, 0),
     }
+}
 
-    (0, 0)
+/// Drops the children with an explicit stack, since the default drop of a deep
+/// tree recurses once per level and overflows the stack.
+impl Drop for TreeNode {
+    fn drop(&mut self) {
+        let mut stack: Vec<Box<TreeNode>> = self
+            .left
+            .take()
+            .into_iter()
+            .chain(self.right.take())
+            .collect();
+        while let Some(mut node) = stack.pop() {
+            stack.extend(node.left.take().into_iter().chain(node.right.take()));
+        }
+    }
 }
 
 #[cfg(test)]
@@ -49,4 +279,131 @@ mod tests {
         
         assert_eq!(count_nodes(&Some(Box::new(root)), 0), (1, 52));
     }
+
+    fn sample() -> TreeNode {
+        let mut root = TreeNode::new(12);
+
+        let mut left_child = TreeNode::new(8);
+        left_child.right = Some(Box::new(TreeNode::new(9)));
+
+        root.left = Some(Box::new(left_child));
+        root.right = Some(Box::new(TreeNode::new(20)));
+        root
+    }
+
+    #[test]
+    fn test_builders() {
+        let root = TreeNode::new(12)
+            .with_left(TreeNode::new(8).with_right(TreeNode::new(9)))
+            .with_right(TreeNode::new(20));
+
+        assert_eq!(root.value(), 12);
+        assert_eq!(root.left().map(TreeNode::value), Some(8));
+        assert!(root.left().unwrap().left().is_none());
+        assert_eq!(root.to_ascii(), sample().to_ascii());
+
+        let replaced = root.with_right(TreeNode::new(30));
+        assert_eq!(replaced.right().map(TreeNode::value), Some(30));
+    }
+
+    #[test]
+    fn test_tree_macro() {
+        assert_eq!(tree!(12, (8, _, 9), 20).to_ascii(), sample().to_ascii());
+        assert_eq!(tree!(-1).value(), -1);
+
+        let root = tree!(1, ((-2), _, (3, (-4), _)), _);
+        assert_eq!(
+            root.to_ascii(),
+            "1
+`-- L: -2
+    `-- R: 3
+        `-- L: -4
+"
+        );
+        assert_eq!(
+            TreeNode::from_parts(1, None, Some(Box::new(TreeNode::new(2)))).to_ascii(),
+            tree!(1, _, 2).to_ascii()
+        );
+    }
+
+    #[test]
+    fn test_matching_nodes() {
+        // 8 has descendants summing to 12, 3 and 9 are below ancestors summing to 20
+        let root = tree!(12, (8, 3, 9), 20);
+        let matches = root.matching_nodes();
+        let ids: Vec<usize> = matches.iter().map(|&(id, _)| id).collect();
+        assert_eq!(ids, [1]);
+        assert_eq!(matches[0].1.value(), 8);
+        assert_eq!(count_nodes(&Some(Box::new(root)), 0).0, 1);
+
+        // -5 has a single descendant 0, and its only ancestor is 0
+        let root = tree!(0, (5, (-2), 7), (-5, 0, _));
+        let matches: Vec<(usize, i32)> = root
+            .matching_nodes()
+            .into_iter()
+            .map(|(id, node)| (id, node.value()))
+            .collect();
+        assert_eq!(matches, [(4, -5)]);
+        assert!(root
+            .to_dot(&[4])
+            .contains("4 [label=\"#4: -5\", style=filled"));
+
+        // The root matches when its descendants sum to zero
+        let root = tree!(5, 2, (-2));
+        assert_eq!(root.matching_nodes()[0].0, 0);
+        assert_eq!(count_nodes(&Some(Box::new(root)), 0).0, 1);
+
+        // A single node has neither ancestors nor descendants
+        assert_eq!(TreeNode::new(1).matching_nodes().len(), 1);
+    }
+
+    #[test]
+    fn test_deep_chain() {
+        let mut zeros = TreeNode::new(0);
+        let mut ones = TreeNode::new(1);
+        for i in 1..1_000_000 {
+            zeros = TreeNode::new(0).with_left(zeros);
+            ones = if i % 2 == 0 {
+                TreeNode::new(1).with_left(ones)
+            } else {
+                TreeNode::new(1).with_right(ones)
+            };
+        }
+
+        // Every node of the zero chain matches; in the chain of ones a node at
+        // depth d needs d == 999_999 - d, which never holds
+        assert_eq!(zeros.matching_nodes().len(), 1_000_000);
+        assert_eq!(count_nodes(&Some(Box::new(zeros)), 0), (1_000_000, 0));
+        assert_eq!(count_nodes(&Some(Box::new(ones)), 0), (0, 1_000_000));
+    }
+
+    #[test]
+    fn test_to_dot() {
+        assert_eq!(
+            sample().to_dot(&[1]),
+            "digraph Tree {
+    node [shape=circle];
+    0 [label=\"#0: 12\"];
+    1 [label=\"#1: 8\", style=filled, fillcolor=gold];
+    0 -> 1 [tailport=sw];
+    2 [label=\"#2: 9\"];
+    1 -> 2 [tailport=se];
+    3 [label=\"#3: 20\"];
+    0 -> 3 [tailport=se];
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_to_ascii() {
+        assert_eq!(
+            sample().to_ascii(),
+            "12
+|-- L: 8
+|   `-- R: 9
+`-- R: 20
+"
+        );
+    }
 }
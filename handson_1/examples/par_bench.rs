@@ -0,0 +1,80 @@
+//! Compares the parallel folds with their sequential counterparts.
+//!
+//! Run with `cargo run --release --example par_bench`.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use handson_1::Tree;
+
+/// Builds a tree of `n` nodes hanging each node from a pseudo-random earlier one.
+fn random_tree(n: usize) -> Tree<u64> {
+    let mut tree = Tree::with_root(0);
+    let mut state: u64 = 7;
+    while tree.len() < n {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        let parent = (state >> 33) as usize % tree.len();
+        let key = (state >> 40) % 1000;
+        let _ = tree.try_add_node(parent, key, state & 1 == 0);
+    }
+    tree
+}
+
+/// Builds a spine of `n` nodes with a leaf hanging from every node.
+fn caterpillar(n: u64) -> Tree<u64> {
+    let mut tree = Tree::with_root(1);
+    let mut last = 0;
+    for key in 2..=n {
+        let _ = tree.try_add_node(last, key * 2, key % 2 == 0);
+        last = tree.try_add_node(last, key, key % 2 == 1).unwrap();
+    }
+    tree
+}
+
+/// Runs `f` a few times and returns its result and the fastest time.
+fn time<T: PartialEq + std::fmt::Debug>(f: impl Fn() -> T) -> (T, Duration) {
+    let mut best = Duration::MAX;
+    let mut result = None;
+    for _ in 0..5 {
+        let start = Instant::now();
+        let value = f();
+        best = best.min(start.elapsed());
+        if let Some(previous) = &result {
+            assert_eq!(previous, &value);
+        }
+        result = Some(value);
+    }
+    (result.unwrap(), best)
+}
+
+fn bench(name: &str, tree: &Tree<u64>) {
+    println!("{name} ({} nodes)", tree.len());
+
+    let (sum, sequential) = time(|| tree.sum());
+    let (par_sum, parallel) = time(|| tree.par_sum());
+    assert_eq!(sum, par_sum);
+    println!("  sum           {sequential:>12?}  par_sum           {parallel:>12?}");
+
+    let (max, sequential) = time(|| tree.max_path_sum());
+    let (par_max, parallel) = time(|| tree.par_max_path_sum());
+    assert_eq!(max, par_max);
+    println!("  max_path_sum  {sequential:>12?}  par_max_path_sum  {parallel:>12?}");
+}
+
+fn main() {
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    println!("{threads} available threads");
+
+    // The sequential folds are recursive, so deep trees need a large stack
+    thread::Builder::new()
+        .stack_size(1 << 30)
+        .spawn(|| {
+            bench("random", &random_tree(2_000_000));
+            bench("caterpillar", &caterpillar(500_000));
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+}
@@ -0,0 +1,289 @@
+use crate::{Numeric, Tree};
+
+/// An index answering subtree sums and path maximums on a [`Tree`], with key updates.
+///
+/// The nodes are laid out in a pre-order where the child with the larger subtree
+/// (the heavy child) is visited first. Every subtree is then a contiguous range
+/// of that order (Euler tour flattening), and the tree splits into heavy chains
+/// that are contiguous too, so any path crosses O(log n) of them (heavy-light
+/// decomposition). Two segment trees over the order answer both kinds of query
+/// in O(log n), and O(log^2 n) for paths.
+///
+/// The index owns its tree, so keys can only be changed through
+/// [`AggregateIndex::set_key`], which keeps both in step. Structural changes
+/// need the tree back from [`AggregateIndex::into_tree`] and a new index.
+pub struct AggregateIndex<K> {
+    tree: Tree<K>,
+    /// The position of each id in the heavy-first pre-order, or `None` for free slots.
+    pos: Vec<Option<usize>>,
+    /// The number of nodes in the subtree rooted at each id.
+    size: Vec<usize>,
+    parent: Vec<Option<usize>>,
+    depth: Vec<usize>,
+    /// The topmost node of the heavy chain containing each id.
+    head: Vec<usize>,
+    sums: SegmentTree<K>,
+    maxes: SegmentTree<Option<K>>,
+}
+
+impl<K: Numeric> AggregateIndex<K> {
+    /// Builds the index for the nodes currently in `tree`, taking ownership of it.
+    pub fn new(tree: Tree<K>) -> Self {
+        let n = tree.nodes.len();
+        let mut parent = vec![None; n];
+        let mut depth = vec![0; n];
+        let mut size = vec![1; n];
+
+        // A first pre-order visit, whose reverse gives the subtree sizes bottom-up
+        let mut order = Vec::with_capacity(tree.len());
+        let mut stack = Vec::from_iter(tree.root);
+        while let Some(id) = stack.pop() {
+            order.push(id);
+            let node = &tree.nodes[id];
+            for child in node.id_left.into_iter().chain(node.id_right) {
+                parent[child] = Some(id);
+                depth[child] = depth[id] + 1;
+                stack.push(child);
+            }
+        }
+        for &id in order.iter().rev() {
+            if let Some(parent) = parent[id] {
+                size[parent] += size[id];
+            }
+        }
+
+        // A second visit, heavy child first, assigns the positions and the chain heads
+        let mut pos = vec![None; n];
+        let mut head = vec![0; n];
+        let mut keys = Vec::with_capacity(order.len());
+        let mut stack: Vec<(usize, usize)> =
+            tree.root.map(|root| (root, root)).into_iter().collect();
+        while let Some((id, chain_head)) = stack.pop() {
+            pos[id] = Some(keys.len());
+            head[id] = chain_head;
+
+            let node = &tree.nodes[id];
+            keys.push(*node.key());
+
+            let mut children: Vec<usize> = node.id_left.into_iter().chain(node.id_right).collect();
+            children.sort_by_key(|&child| size[child]);
+            if let Some(heavy) = children.pop() {
+                // The light child is pushed first, so the heavy one is visited next
+                for light in children {
+                    stack.push((light, light));
+                }
+                stack.push((heavy, chain_head));
+            }
+        }
+
+        Self {
+            tree,
+            pos,
+            size,
+            parent,
+            depth,
+            head,
+            sums: SegmentTree::new(&keys, K::ZERO, |a, b| a + b),
+            maxes: SegmentTree::new(
+                &keys.iter().map(|&key| Some(key)).collect::<Vec<_>>(),
+                None,
+                Option::max,
+            ),
+        }
+    }
+
+    /// Returns the indexed tree, e.g. to compute its `sum` or `max_path_sum`.
+    pub fn tree(&self) -> &Tree<K> {
+        &self.tree
+    }
+
+    /// Consumes the index and returns the tree, with every key set so far.
+    pub fn into_tree(self) -> Tree<K> {
+        self.tree
+    }
+
+    /// Returns the sum of the keys in the subtree rooted at `id`,
+    /// or `None` if the node is not in the tree.
+    pub fn subtree_sum(&self, id: usize) -> Option<K> {
+        let start = self.pos.get(id).copied().flatten()?;
+        Some(self.sums.query(start, start + self.size[id]))
+    }
+
+    /// Returns the greatest key on the path between the nodes `u` and `v`,
+    /// both included, or `None` if either of them is not in the tree.
+    pub fn path_max(&self, mut u: usize, mut v: usize) -> Option<K> {
+        let pos = |id: usize| self.pos.get(id).copied().flatten();
+        pos(u)?;
+        pos(v)?;
+
+        // Climb one heavy chain at a time, always from the one with the deeper head
+        let mut max = None;
+        while self.head[u] != self.head[v] {
+            if self.depth[self.head[u]] < self.depth[self.head[v]] {
+                std::mem::swap(&mut u, &mut v);
+            }
+            let head = self.head[u];
+            max = max.max(self.maxes.query(pos(head)?, pos(u)? + 1));
+            u = self.parent[head]?;
+        }
+
+        let (start, end) = (pos(u)?.min(pos(v)?), pos(u)?.max(pos(v)?));
+        max.max(self.maxes.query(start, end + 1))
+    }
+
+    /// Sets the key of the node `id` to `key` in both the tree and the index.
+    /// Returns `false` without changes if the node is not in the tree.
+    pub fn set_key(&mut self, id: usize, key: K) -> bool {
+        let Some(pos) = self.pos.get(id).copied().flatten() else {
+            return false;
+        };
+
+        self.tree.nodes[id].key = Some(key);
+        self.sums.set(pos, key);
+        self.maxes.set(pos, Some(key));
+        true
+    }
+}
+
+/// A bottom-up segment tree over values combined by an associative function.
+struct SegmentTree<T> {
+    len: usize,
+    /// The leaves are stored at `len..2 * len`, node `i` combines `2 * i` and `2 * i + 1`.
+    data: Vec<T>,
+    identity: T,
+    combine: fn(T, T) -> T,
+}
+
+impl<T: Copy> SegmentTree<T> {
+    fn new(values: &[T], identity: T, combine: fn(T, T) -> T) -> Self {
+        let len = values.len();
+        let mut data = vec![identity; 2 * len];
+        data[len..].copy_from_slice(values);
+        for i in (1..len).rev() {
+            data[i] = combine(data[2 * i], data[2 * i + 1]);
+        }
+
+        Self {
+            len,
+            data,
+            identity,
+            combine,
+        }
+    }
+
+    fn set(&mut self, index: usize, value: T) {
+        let mut i = index + self.len;
+        self.data[i] = value;
+        while i > 1 {
+            i /= 2;
+            self.data[i] = (self.combine)(self.data[2 * i], self.data[2 * i + 1]);
+        }
+    }
+
+    /// Combines the values in the range `start..end`.
+    fn query(&self, start: usize, end: usize) -> T {
+        let (mut left, mut right) = (self.identity, self.identity);
+        let (mut l, mut r) = (start + self.len, end + self.len);
+
+        while l < r {
+            if l % 2 == 1 {
+                left = (self.combine)(left, self.data[l]);
+                l += 1;
+            }
+            if r % 2 == 1 {
+                r -= 1;
+                right = (self.combine)(self.data[r], right);
+            }
+            l /= 2;
+            r /= 2;
+        }
+
+        (self.combine)(left, right)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Tree<i64> {
+        "[10, 5, 22, null, 7, 20, null, -4, 1]".parse().unwrap()
+    }
+
+    #[test]
+    fn test_subtree_sum() {
+        let index = AggregateIndex::new(sample());
+
+        assert_eq!(index.subtree_sum(0), Some(index.tree().sum()));
+        assert_eq!(index.subtree_sum(1), Some(9));
+        assert_eq!(index.subtree_sum(2), Some(42));
+        assert_eq!(index.subtree_sum(3), Some(4));
+        assert_eq!(index.subtree_sum(5), Some(-4));
+        assert_eq!(index.subtree_sum(7), None);
+    }
+
+    #[test]
+    fn test_path_max() {
+        let index = AggregateIndex::new(sample());
+
+        assert_eq!(index.path_max(5, 6), Some(7));
+        assert_eq!(index.path_max(5, 1), Some(7));
+        assert_eq!(index.path_max(6, 4), Some(22));
+        assert_eq!(index.path_max(5, 5), Some(-4));
+        assert_eq!(index.path_max(0, 3), Some(10));
+        assert_eq!(index.path_max(0, 9), None);
+    }
+
+    #[test]
+    fn test_set_key() {
+        let mut index = AggregateIndex::new(sample());
+
+        assert!(index.set_key(3, 100));
+        assert_eq!(index.subtree_sum(1), Some(102));
+        assert_eq!(index.subtree_sum(0), Some(154));
+        assert_eq!(index.path_max(5, 4), Some(100));
+
+        assert!(index.set_key(3, -100));
+        assert_eq!(index.path_max(5, 6), Some(1));
+        assert_eq!(index.path_max(6, 4), Some(22));
+        assert!(!index.set_key(42, 0));
+
+        // The tree sees the same keys as the index
+        assert_eq!(index.tree().sum(), -46);
+        assert_eq!(index.subtree_sum(0), Some(index.tree().sum()));
+        let tree = index.into_tree();
+        assert_eq!(tree.cursor(3).unwrap().key(), &-100);
+        assert_eq!(tree.max_path().map(|(sum, _)| sum), Some(57));
+    }
+
+    #[test]
+    fn test_path_max_matches_naive() {
+        let mut tree: Tree<i64> = Tree::with_root(0);
+        let mut ids = vec![0];
+        for key in 1..2_000 {
+            let parent = ids[(key as usize * 7) % ids.len()];
+            let is_left = tree.nodes[parent].id_left.is_none();
+            if is_left || tree.nodes[parent].id_right.is_none() {
+                ids.push(tree.add_node(parent, (key * 37) % 101 - 50, is_left));
+            }
+        }
+        let lca = crate::LcaIndex::new(&tree);
+        let index = AggregateIndex::new(tree);
+        let tree = index.tree();
+
+        for &u in ids.iter().step_by(13) {
+            for &v in ids.iter().step_by(17) {
+                // Walk both nodes up to their common ancestor
+                let ancestor = lca.lca(u, v).unwrap();
+                let mut expected = tree.nodes[ancestor].key;
+                for mut id in [u, v] {
+                    while id != ancestor {
+                        expected = expected.max(tree.nodes[id].key);
+                        id = index.parent[id].unwrap();
+                    }
+                }
+                assert_eq!(index.path_max(u, v), expected);
+            }
+        }
+    }
+}
@@ -0,0 +1,394 @@
+use std::cmp::Ordering;
+
+use crate::{DuplicatePolicy, Tree};
+
+/// A self-balancing BST stored in a [`Tree`] arena, kept balanced with AVL rotations.
+///
+/// Keys are unique: inserting a key that is already present does nothing.
+/// The height of the tree stays O(log n) whatever the insertion order, so the
+/// recursive aggregates of the underlying [`Tree`] never go deep.
+pub struct AvlTree<K> {
+    tree: Tree<K>,
+    /// The height of the subtree rooted at each id, where a leaf has height 1.
+    heights: Vec<usize>,
+}
+
+/// The first broken AVL invariant found by [`AvlTree::check_invariants`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AvlViolation {
+    /// The node breaks the strict BST property.
+    NotBst(usize),
+    /// The stored height of the node does not match the height of its subtree.
+    WrongHeight(usize),
+    /// The heights of the subtrees of the node differ by more than one.
+    Unbalanced(usize),
+}
+
+impl<K> Default for AvlTree<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K> AvlTree<K> {
+    /// Creates an empty AVL tree.
+    pub fn new() -> Self {
+        Self {
+            tree: Tree::new(),
+            heights: Vec::new(),
+        }
+    }
+
+    /// Returns the underlying tree, e.g. to compute its `sum` or `max_path_sum`.
+    pub fn tree(&self) -> &Tree<K> {
+        &self.tree
+    }
+
+    /// Consumes the AVL tree and returns the underlying tree.
+    pub fn into_tree(self) -> Tree<K> {
+        self.tree
+    }
+
+    /// Returns the number of keys in the tree.
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    /// Returns `true` if the tree has no keys.
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// Returns the number of nodes on the longest path from the root to a leaf.
+    pub fn height(&self) -> usize {
+        self.height_of(self.tree.root)
+    }
+
+    fn height_of(&self, node_id: Option<usize>) -> usize {
+        node_id.map_or(0, |id| self.heights[id])
+    }
+
+    /// A private function that recomputes the height of the node `id` from its children.
+    fn update_height(&mut self, id: usize) {
+        let node = &self.tree.nodes[id];
+        self.heights[id] = 1 + self
+            .height_of(node.id_left)
+            .max(self.height_of(node.id_right));
+    }
+
+    /// A private function that rotates the subtree rooted at `id` to the left
+    /// and returns the id of its new root.
+    fn rotate_left(&mut self, id: usize) -> usize {
+        let pivot = self
+            .tree
+            .rotate_left(id)
+            .expect("Rotation needs a right child");
+
+        self.update_height(id);
+        self.update_height(pivot);
+        pivot
+    }
+
+    /// A private function that rotates the subtree rooted at `id` to the right
+    /// and returns the id of its new root.
+    fn rotate_right(&mut self, id: usize) -> usize {
+        let pivot = self
+            .tree
+            .rotate_right(id)
+            .expect("Rotation needs a left child");
+
+        self.update_height(id);
+        self.update_height(pivot);
+        pivot
+    }
+
+    /// A private function that restores the balance of the subtree rooted at `id`,
+    /// whose children are already balanced, and returns the id of its new root.
+    fn rebalance(&mut self, id: usize) -> usize {
+        self.update_height(id);
+
+        let node = &self.tree.nodes[id];
+        let (left, right) = (node.id_left, node.id_right);
+        let left_height = self.height_of(left);
+        let right_height = self.height_of(right);
+
+        if left_height > right_height + 1 {
+            let left = left.unwrap();
+            let left_node = &self.tree.nodes[left];
+            if self.height_of(left_node.id_left) < self.height_of(left_node.id_right) {
+                self.rotate_left(left);
+            }
+            return self.rotate_right(id);
+        }
+
+        if right_height > left_height + 1 {
+            let right = right.unwrap();
+            let right_node = &self.tree.nodes[right];
+            if self.height_of(right_node.id_right) < self.height_of(right_node.id_left) {
+                self.rotate_right(right);
+            }
+            return self.rotate_left(id);
+        }
+
+        id
+    }
+
+    /// A private function that rebalances the nodes on `path`, from the root
+    /// down to the last modified node, walking it bottom-up.
+    fn rebalance_path(&mut self, path: &[usize]) {
+        for &id in path.iter().rev() {
+            self.rebalance(id);
+        }
+    }
+}
+
+impl<K: Ord> AvlTree<K> {
+    /// Inserts `key` in the tree. Returns `false` if the key was already present.
+    pub fn insert(&mut self, key: K) -> bool {
+        let mut path = Vec::new();
+        let mut current = self.tree.root;
+        let mut is_left = false;
+
+        while let Some(id) = current {
+            let node = &self.tree.nodes[id];
+            is_left = match key.cmp(node.key()) {
+                Ordering::Less => true,
+                Ordering::Equal => return false,
+                Ordering::Greater => false,
+            };
+            path.push(id);
+            current = if is_left { node.id_left } else { node.id_right };
+        }
+
+        let id = match path.last() {
+            Some(&parent_id) => self.tree.add_node(parent_id, key, is_left),
+            None => self.tree.add_root(key),
+        };
+        if id >= self.heights.len() {
+            self.heights.resize(id + 1, 0);
+        }
+        self.heights[id] = 1;
+
+        self.rebalance_path(&path);
+        true
+    }
+
+    /// Returns `true` if the tree contains `key`.
+    pub fn contains(&self, key: &K) -> bool {
+        self.tree.find(key).is_some()
+    }
+
+    /// Removes `key` from the tree and returns it, or `None` if it was not present.
+    pub fn remove(&mut self, key: &K) -> Option<K> {
+        let mut path = Vec::new();
+        let mut current = self.tree.root;
+
+        let id = loop {
+            let id = current?;
+            let node = &self.tree.nodes[id];
+            current = match key.cmp(node.key()) {
+                Ordering::Less => node.id_left,
+                Ordering::Equal => break id,
+                Ordering::Greater => node.id_right,
+            };
+            path.push(id);
+        };
+
+        // A node with two children is replaced by its in-order predecessor,
+        // which has no right child, so that every other key keeps its id
+        let node = &self.tree.nodes[id];
+        let key = match (node.id_left, node.id_right) {
+            (Some(left), Some(right)) => {
+                let position = path.len();
+                path.push(id);
+                let mut predecessor = left;
+                while let Some(right) = self.tree.nodes[predecessor].id_right {
+                    path.push(predecessor);
+                    predecessor = right;
+                }
+
+                let inner = self.tree.nodes[predecessor].id_left;
+                self.tree
+                    .replace_link(path.last().copied(), predecessor, inner);
+                let left = self.tree.nodes[id].id_left;
+                self.tree.set_child(predecessor, true, left);
+                self.tree.set_child(predecessor, false, Some(right));
+
+                let parent_id = self.tree.nodes[id].id_parent;
+                self.tree.replace_link(parent_id, id, Some(predecessor));
+                path[position] = predecessor;
+                self.tree.release(id)
+            }
+            (left, right) => {
+                self.tree
+                    .replace_link(path.last().copied(), id, left.or(right));
+                self.tree.release(id)
+            }
+        };
+
+        self.rebalance_path(&path);
+        Some(key)
+    }
+
+    /// Checks the AVL invariants: the tree is a BST without duplicates, every
+    /// stored height is correct and the heights of the subtrees of every node
+    /// differ by at most one. Returns the first violation found.
+    pub fn check_invariants(&self) -> Result<(), AvlViolation> {
+        if let Some(violation) = self.tree.bst_violation(DuplicatePolicy::Strict) {
+            return Err(AvlViolation::NotBst(violation.id));
+        }
+
+        self.rec_check(self.tree.root).map(|_| ())
+    }
+
+    /// A private recursive function that checks the heights in the subtree
+    /// rooted at `node_id` and returns its actual height.
+    fn rec_check(&self, node_id: Option<usize>) -> Result<usize, AvlViolation> {
+        let Some(id) = node_id else {
+            return Ok(0);
+        };
+        let node = &self.tree.nodes[id];
+
+        let left_height = self.rec_check(node.id_left)?;
+        let right_height = self.rec_check(node.id_right)?;
+        let height = 1 + left_height.max(right_height);
+
+        if self.heights[id] != height {
+            return Err(AvlViolation::WrongHeight(id));
+        }
+        if left_height.abs_diff(right_height) > 1 {
+            return Err(AvlViolation::Unbalanced(id));
+        }
+
+        Ok(height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A minimal linear congruential generator, to shuffle keys without dependencies.
+    fn shuffled(n: u32) -> Vec<u32> {
+        let mut keys: Vec<u32> = (0..n).collect();
+        let mut state: u64 = 42;
+        for i in (1..keys.len()).rev() {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            keys.swap(i, (state >> 33) as usize % (i + 1));
+        }
+        keys
+    }
+
+    /// The maximum height of an AVL tree with `n` nodes.
+    fn max_height(n: usize) -> usize {
+        (1.45 * ((n + 2) as f64).log2()).floor() as usize
+    }
+
+    #[test]
+    fn test_sorted_inserts_stay_balanced() {
+        let mut avl = AvlTree::new();
+        for key in 0..10_000u32 {
+            assert!(avl.insert(key));
+        }
+
+        assert_eq!(avl.check_invariants(), Ok(()));
+        assert_eq!(avl.len(), 10_000);
+        assert!(avl.height() <= max_height(10_000));
+        assert!(avl.tree().is_bst());
+        assert_eq!(avl.tree().sum_as::<u64>(), 49_995_000);
+    }
+
+    #[test]
+    fn test_insert_duplicate() {
+        let mut avl = AvlTree::new();
+        assert!(avl.insert(5));
+        assert!(!avl.insert(5));
+        assert_eq!(avl.len(), 1);
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut avl = AvlTree::new();
+        for key in shuffled(1000) {
+            avl.insert(key);
+        }
+
+        for key in (0..1000).step_by(3) {
+            assert_eq!(avl.remove(&key), Some(key));
+            assert_eq!(avl.check_invariants(), Ok(()));
+        }
+
+        assert_eq!(avl.remove(&0), None);
+        assert_eq!(avl.len(), 666);
+        assert!(avl.contains(&1));
+        assert!(!avl.contains(&3));
+        assert!(avl.height() <= max_height(666));
+    }
+
+    #[test]
+    fn test_remove_keeps_ids() {
+        let mut avl = AvlTree::new();
+        for key in shuffled(200) {
+            avl.insert(key);
+        }
+        let handles: Vec<_> = (0..200)
+            .map(|key| avl.tree().handle(avl.tree().find(&key).unwrap()).unwrap())
+            .collect();
+
+        for key in (0..200).step_by(2) {
+            avl.remove(&key);
+            assert_eq!(avl.check_invariants(), Ok(()));
+        }
+        for (key, &handle) in (0..).zip(&handles) {
+            let id = avl.tree().resolve(handle);
+            if key % 2 == 0 {
+                assert_eq!(id, None);
+            } else {
+                assert_eq!(id, Some(handle.id()));
+                assert_eq!(avl.tree().find(&key), id);
+            }
+        }
+    }
+
+    #[test]
+    fn test_remove_all_then_reuse() {
+        let mut avl = AvlTree::new();
+        for key in 0..100u32 {
+            avl.insert(key);
+        }
+        for key in shuffled(100) {
+            assert_eq!(avl.remove(&key), Some(key));
+        }
+        assert!(avl.is_empty());
+        assert_eq!(avl.height(), 0);
+
+        for key in (0..50u32).rev() {
+            avl.insert(key);
+        }
+        assert_eq!(avl.check_invariants(), Ok(()));
+        assert_eq!(avl.tree().range(..).count(), 50);
+    }
+
+    #[test]
+    fn test_check_invariants_detects_violations() {
+        let mut avl = AvlTree::new();
+        avl.insert(2);
+        avl.insert(1);
+
+        avl.heights[0] = 5;
+        assert_eq!(avl.check_invariants(), Err(AvlViolation::WrongHeight(0)));
+
+        avl.heights[0] = 2;
+        let id = avl.tree.add_node(1, 0, true);
+        avl.heights.push(1);
+        avl.heights[1] = 2;
+        avl.heights[0] = 3;
+        assert_eq!(id, 2);
+        assert_eq!(avl.check_invariants(), Err(AvlViolation::Unbalanced(0)));
+
+        avl.tree.nodes[2].key = Some(7);
+        assert_eq!(avl.check_invariants(), Err(AvlViolation::NotBst(2)));
+    }
+}
@@ -0,0 +1,197 @@
+use crate::Tree;
+
+/// Where a node goes in a balanced layout: the index of its key in the
+/// sorted sequence, and the index of its parent with its side, if any.
+type Placement = (usize, Option<(usize, bool)>);
+
+/// A private function that lays out a minimum-height BST over the sorted
+/// sequence `keys`, in pre-order, so every parent comes before its children.
+///
+/// Each subtree takes its middle key as the root; when the middle key has
+/// equal neighbours on its right, the last of them is taken instead, so
+/// that equal keys always end up in left subtrees.
+fn balanced_layout<K: Ord>(keys: &[&K]) -> Vec<Placement> {
+    let mut layout = Vec::with_capacity(keys.len());
+    // Each entry holds a half-open range of `keys` and where its root hangs
+    let mut stack = vec![(0, keys.len(), None)];
+
+    while let Some((start, end, parent)) = stack.pop() {
+        if start == end {
+            continue;
+        }
+
+        let mut mid = start + (end - start) / 2;
+        while mid + 1 < end && keys[mid + 1] == keys[mid] {
+            mid += 1;
+        }
+
+        layout.push((mid, parent));
+        stack.push((mid + 1, end, Some((mid, false))));
+        stack.push((start, mid, Some((mid, true))));
+    }
+
+    layout
+}
+
+impl<K: Ord + Clone> Tree<K> {
+    /// Builds a BST of minimum height over `keys`, which passes [`Tree::is_bst`].
+    /// Equal keys are placed in left subtrees, so many duplicates make the tree deeper.
+    /// The ids of the nodes follow the pre-order of the tree, starting from 0 for the root.
+    ///
+    /// # Panics
+    /// Panics if `keys` is not sorted.
+    pub fn from_sorted(keys: &[K]) -> Self {
+        assert!(
+            keys.windows(2).all(|pair| pair[0] <= pair[1]),
+            "Keys are not sorted"
+        );
+
+        let mut tree = Tree::new();
+        let mut ids = vec![0; keys.len()];
+        let refs: Vec<&K> = keys.iter().collect();
+
+        for (index, parent) in balanced_layout(&refs) {
+            let key = keys[index].clone();
+            ids[index] = match parent {
+                Some((parent, is_left)) => tree.link_new_child(ids[parent], key, is_left),
+                None => tree.add_root(key),
+            };
+        }
+
+        tree
+    }
+}
+
+impl<K: Ord> Tree<K> {
+    /// Rebuilds the BST in place into one of minimum height, laid out like
+    /// [`Tree::from_sorted`]. Every key stays at its id and the in-order
+    /// sequence of keys does not change; only the links between nodes do.
+    pub fn rebalance(&mut self) {
+        // The ids of the nodes in order, collected with an explicit stack
+        let mut ids = Vec::with_capacity(self.len());
+        let mut stack = Vec::new();
+        let mut current = self.root;
+        while current.is_some() || !stack.is_empty() {
+            while let Some(id) = current {
+                stack.push(id);
+                current = self.nodes[id].id_left;
+            }
+            let id = stack.pop().unwrap();
+            ids.push(id);
+            current = self.nodes[id].id_right;
+        }
+
+        let keys: Vec<&K> = ids.iter().map(|&id| self.nodes[id].key()).collect();
+        let layout = balanced_layout(&keys);
+
+        for &id in &ids {
+            let node = &mut self.nodes[id];
+            node.id_left = None;
+            node.id_right = None;
+        }
+        for (index, parent) in layout {
+            match parent {
+                Some((parent, is_left)) => self.set_child(ids[parent], is_left, Some(ids[index])),
+                None => {
+                    self.root = Some(ids[index]);
+                    self.nodes[ids[index]].id_parent = None;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_sorted() {
+        let keys: Vec<u32> = (1..=7).collect();
+        let tree = Tree::from_sorted(&keys);
+
+        assert_eq!(
+            tree.to_level_order(),
+            [
+                Some(4),
+                Some(2),
+                Some(6),
+                Some(1),
+                Some(3),
+                Some(5),
+                Some(7)
+            ]
+        );
+        assert!(tree.metrics().is_perfect);
+        assert_eq!(tree.range(..).copied().collect::<Vec<_>>(), keys);
+        assert!(Tree::<u32>::from_sorted(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_from_sorted_large() {
+        let keys: Vec<u32> = (0..100_000).map(|key| key * 3).collect();
+        let tree = Tree::from_sorted(&keys);
+
+        let metrics = tree.metrics();
+        assert_eq!(metrics.height, 17);
+        assert!(metrics.is_balanced);
+        assert!(tree.is_bst());
+    }
+
+    #[test]
+    fn test_from_sorted_duplicates() {
+        let keys = [1, 2, 2, 2, 3, 5, 5];
+        let tree = Tree::from_sorted(&keys);
+
+        assert!(tree.is_bst());
+        assert_eq!(tree.range(..).copied().collect::<Vec<_>>(), keys);
+        // The middle key 2 has equal keys on its right, so the last of them is the root
+        assert_eq!(tree.nodes[tree.root().unwrap()].key(), &2);
+        assert_eq!(tree.range(3..).count(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "Keys are not sorted")]
+    fn test_from_sorted_unsorted() {
+        Tree::from_sorted(&[2, 1]);
+    }
+
+    #[test]
+    fn test_rebalance() {
+        let mut tree = Tree::new();
+        for key in 0..1000u32 {
+            tree.insert(key);
+        }
+        tree.insert(500);
+        assert_eq!(tree.metrics().height, 1000);
+
+        tree.rebalance();
+        let metrics = tree.metrics();
+        assert_eq!(metrics.size, 1001);
+        assert_eq!(metrics.height, 10);
+        assert!(tree.is_bst());
+        assert_eq!(tree.find(&999), Some(999));
+        assert_eq!(tree.range(500..=500).count(), 2);
+        assert_eq!(tree.sum(), 999 * 1000 / 2 + 500);
+
+        // Parent links follow the new shape
+        let leaf = tree.find(&0).unwrap();
+        let root = tree.root().unwrap();
+        let mut cursor = tree.cursor(leaf).unwrap();
+        while let Some(parent) = cursor.parent() {
+            cursor = parent;
+        }
+        assert_eq!(cursor.id(), root);
+    }
+
+    #[test]
+    fn test_rebalance_empty_and_single() {
+        let mut tree = Tree::<u32>::new();
+        tree.rebalance();
+        assert!(tree.is_empty());
+
+        let mut tree = Tree::with_root(3);
+        tree.rebalance();
+        assert_eq!(tree.root(), Some(0));
+    }
+}
@@ -0,0 +1,320 @@
+use std::cmp::Ordering;
+use std::ops::{Bound, RangeBounds};
+
+use crate::{ReattachPolicy, Tree};
+
+/// Binary search tree operations. They keep the rule checked by [`Tree::is_bst`]:
+/// keys smaller than or equal to a node's key go in its left subtree,
+/// greater keys in its right subtree.
+impl<K: Ord> Tree<K> {
+    /// Inserts a new node with the specified `key` in the BST and returns its id.
+    /// A key equal to an existing one is placed in the left subtree of that node.
+    pub fn insert(&mut self, key: K) -> usize {
+        let Some(mut id) = self.root else {
+            return self.add_root(key);
+        };
+
+        loop {
+            let node = &self.nodes[id];
+            let is_left = key <= *node.key();
+            let child = if is_left { node.id_left } else { node.id_right };
+
+            match child {
+                Some(child) => id = child,
+                None => return self.add_node(id, key, is_left),
+            }
+        }
+    }
+
+    /// Returns the id of a node with the specified `key`, or `None` if there is none.
+    pub fn find(&self, key: &K) -> Option<usize> {
+        let mut current = self.root;
+
+        while let Some(id) = current {
+            let node = &self.nodes[id];
+            current = match key.cmp(node.key()) {
+                Ordering::Less => node.id_left,
+                Ordering::Equal => return Some(id),
+                Ordering::Greater => node.id_right,
+            };
+        }
+
+        None
+    }
+
+    /// Returns `true` if the BST contains a node with the specified `key`.
+    pub fn contains(&self, key: &K) -> bool {
+        self.find(key).is_some()
+    }
+
+    /// Removes one node with the specified `key` from the BST and returns its key,
+    /// or `None` if there is no such node.
+    pub fn remove(&mut self, key: &K) -> Option<K> {
+        let id = self.find(key)?;
+        let node = &self.nodes[id];
+
+        let (Some(left), Some(right)) = (node.id_left, node.id_right) else {
+            return Some(self.remove_node(id, ReattachPolicy::PromoteLeft));
+        };
+
+        // A node with two children is replaced by its in-order predecessor,
+        // which has no right child, so that every other key keeps its id
+        let predecessor = self.rightmost(left);
+        if predecessor != left {
+            let inner = self.nodes[predecessor].id_left;
+            let predecessor_parent = self.nodes[predecessor].id_parent;
+            self.replace_link(predecessor_parent, predecessor, inner);
+            self.set_child(predecessor, true, Some(left));
+        }
+        self.set_child(predecessor, false, Some(right));
+
+        let parent_id = self.nodes[id].id_parent;
+        self.replace_link(parent_id, id, Some(predecessor));
+        Some(self.release(id))
+    }
+
+    /// Returns the smallest key in the BST, or `None` if it is empty.
+    pub fn min(&self) -> Option<&K> {
+        let mut id = self.root?;
+        while let Some(left) = self.nodes[id].id_left {
+            id = left;
+        }
+        Some(self.nodes[id].key())
+    }
+
+    /// Returns the greatest key in the BST, or `None` if it is empty.
+    pub fn max(&self) -> Option<&K> {
+        Some(self.nodes[self.rightmost(self.root?)].key())
+    }
+
+    /// Returns the greatest key smaller than or equal to `key`, if any.
+    pub fn floor(&self, key: &K) -> Option<&K> {
+        let mut current = self.root;
+        let mut floor = None;
+
+        while let Some(id) = current {
+            let node = &self.nodes[id];
+            if node.key() <= key {
+                floor = Some(node.key());
+                current = node.id_right;
+            } else {
+                current = node.id_left;
+            }
+        }
+
+        floor
+    }
+
+    /// Returns the smallest key greater than or equal to `key`, if any.
+    pub fn ceiling(&self, key: &K) -> Option<&K> {
+        let mut current = self.root;
+        let mut ceiling = None;
+
+        while let Some(id) = current {
+            let node = &self.nodes[id];
+            if node.key() >= key {
+                ceiling = Some(node.key());
+                current = node.id_left;
+            } else {
+                current = node.id_right;
+            }
+        }
+
+        ceiling
+    }
+
+    /// Returns an iterator over the keys of the BST that lie in `range`, in order.
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Range<'_, K, R> {
+        let mut iter = Range {
+            tree: self,
+            stack: Vec::new(),
+            range,
+        };
+        iter.push_left(self.root);
+        iter
+    }
+
+    /// A private function that returns the id of the node with the greatest key
+    /// in the subtree rooted at `id`.
+    fn rightmost(&self, mut id: usize) -> usize {
+        while let Some(right) = self.nodes[id].id_right {
+            id = right;
+        }
+        id
+    }
+}
+
+/// An iterator over a range of keys of a BST, created by [`Tree::range`].
+pub struct Range<'a, K, R> {
+    tree: &'a Tree<K>,
+    stack: Vec<usize>,
+    range: R,
+}
+
+impl<K: Ord, R: RangeBounds<K>> Range<'_, K, R> {
+    /// Pushes the nodes along the left spine of the subtree rooted at `node_id`,
+    /// skipping the ones below the start of the range together with their left subtree.
+    fn push_left(&mut self, mut node_id: Option<usize>) {
+        while let Some(id) = node_id {
+            let node = &self.tree.nodes[id];
+            let above_start = match self.range.start_bound() {
+                Bound::Included(start) => node.key() >= start,
+                Bound::Excluded(start) => node.key() > start,
+                Bound::Unbounded => true,
+            };
+
+            if above_start {
+                self.stack.push(id);
+                node_id = node.id_left;
+            } else {
+                node_id = node.id_right;
+            }
+        }
+    }
+}
+
+impl<'a, K: Ord, R: RangeBounds<K>> Iterator for Range<'a, K, R> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let id = self.stack.pop()?;
+        let tree = self.tree;
+        let node = &tree.nodes[id];
+
+        // Keys come out in order, so the first one past the end stops the iteration
+        if !self.range.contains(node.key()) {
+            self.stack.clear();
+            return None;
+        }
+
+        self.push_left(node.id_right);
+        Some(node.key())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build(keys: &[i32]) -> Tree<i32> {
+        let mut tree = Tree::new();
+        for &key in keys {
+            tree.insert(key);
+        }
+        tree
+    }
+
+    fn keys<R: RangeBounds<i32>>(iter: Range<'_, i32, R>) -> Vec<i32> {
+        iter.copied().collect()
+    }
+
+    #[test]
+    fn test_insert_keeps_bst() {
+        let tree = build(&[10, 5, 22, 7, 20, 5, 10, 1]);
+        assert!(tree.is_bst());
+        assert_eq!(tree.len(), 8);
+        assert_eq!(tree.sum(), 80);
+    }
+
+    #[test]
+    fn test_contains() {
+        let tree = build(&[10, 5, 22, 7]);
+        assert!(tree.contains(&7));
+        assert!(tree.contains(&10));
+        assert!(!tree.contains(&8));
+        assert!(!Tree::new().contains(&8));
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut tree = build(&[10, 5, 22, 3, 7, 20, 30, 6]);
+
+        assert_eq!(tree.remove(&10), Some(10));
+        assert!(tree.is_bst());
+        assert!(!tree.contains(&10));
+
+        assert_eq!(tree.remove(&5), Some(5));
+        assert_eq!(tree.remove(&30), Some(30));
+        assert_eq!(tree.remove(&30), None);
+        assert!(tree.is_bst());
+        assert_eq!(keys(tree.range(..)), [3, 6, 7, 20, 22]);
+    }
+
+    #[test]
+    fn test_remove_keeps_ids() {
+        let mut tree = Tree::new();
+        let ten = tree.insert(10);
+        let five = tree.insert(5);
+        let twenty = tree.insert(20);
+        let seven = tree.insert(7);
+        let handles: Vec<_> = [ten, five, twenty, seven]
+            .iter()
+            .map(|&id| tree.handle(id).unwrap())
+            .collect();
+
+        assert_eq!(tree.remove(&10), Some(10));
+        assert!(tree.is_bst());
+        assert_eq!(tree.resolve(handles[0]), None);
+        for (&handle, (id, key)) in handles[1..]
+            .iter()
+            .zip([(five, 5), (twenty, 20), (seven, 7)])
+        {
+            assert_eq!(tree.resolve(handle), Some(id));
+            assert_eq!(tree.find(&key), Some(id));
+        }
+        assert_eq!(tree.root(), Some(seven));
+
+        // The predecessor is the left child itself
+        assert_eq!(tree.remove(&7), Some(7));
+        assert_eq!(tree.root(), Some(five));
+        assert_eq!(tree.resolve(handles[2]), Some(twenty));
+        assert_eq!(keys(tree.range(..)), [5, 20]);
+    }
+
+    #[test]
+    fn test_remove_duplicates() {
+        let mut tree = build(&[4, 4, 2, 4]);
+        assert_eq!(tree.remove(&4), Some(4));
+        assert!(tree.is_bst());
+        assert_eq!(keys(tree.range(..)), [2, 4, 4]);
+
+        while tree.remove(&4).is_some() {}
+        assert_eq!(keys(tree.range(..)), [2]);
+    }
+
+    #[test]
+    fn test_min_max() {
+        let tree = build(&[10, 5, 22, 7, 20]);
+        assert_eq!(tree.min(), Some(&5));
+        assert_eq!(tree.max(), Some(&22));
+
+        let empty: Tree<i32> = Tree::new();
+        assert_eq!(empty.min(), None);
+        assert_eq!(empty.max(), None);
+    }
+
+    #[test]
+    fn test_floor_ceiling() {
+        let tree = build(&[10, 5, 22, 7, 20]);
+        assert_eq!(tree.floor(&8), Some(&7));
+        assert_eq!(tree.floor(&10), Some(&10));
+        assert_eq!(tree.floor(&4), None);
+        assert_eq!(tree.ceiling(&8), Some(&10));
+        assert_eq!(tree.ceiling(&20), Some(&20));
+        assert_eq!(tree.ceiling(&23), None);
+    }
+
+    #[test]
+    fn test_range() {
+        let tree = build(&[10, 5, 22, 7, 20, 5, 1, 30]);
+
+        assert_eq!(keys(tree.range(..)), [1, 5, 5, 7, 10, 20, 22, 30]);
+        assert_eq!(keys(tree.range(5..=20)), [5, 5, 7, 10, 20]);
+        assert_eq!(keys(tree.range(5..20)), [5, 5, 7, 10]);
+        assert_eq!(
+            keys(tree.range((Bound::Excluded(5), Bound::Unbounded))),
+            [7, 10, 20, 22, 30]
+        );
+        assert_eq!(keys(tree.range(11..20)), []);
+    }
+}
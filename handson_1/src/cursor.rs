@@ -0,0 +1,332 @@
+use crate::{Tree, TreeError};
+
+/// The side a node hangs on below its parent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+/// A read-only cursor on a node of a [`Tree`], created by [`Tree::cursor`].
+/// Moving the cursor returns a new cursor, so several positions can be kept at once.
+pub struct Cursor<'a, K> {
+    tree: &'a Tree<K>,
+    id: usize,
+}
+
+// Derived impls would require `K: Clone`
+impl<K> Clone for Cursor<'_, K> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<K> Copy for Cursor<'_, K> {}
+
+impl<'a, K> Cursor<'a, K> {
+    /// Returns the id of the node under the cursor.
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    /// Returns the key of the node under the cursor.
+    pub fn key(&self) -> &'a K {
+        self.tree.nodes[self.id].key()
+    }
+
+    /// Returns the side the node hangs on below its parent, or `None` for the root.
+    pub fn side(&self) -> Option<Side> {
+        self.tree.side(self.id)
+    }
+
+    /// Returns a cursor on the parent of the node, or `None` for the root.
+    pub fn parent(&self) -> Option<Self> {
+        self.at(self.tree.nodes[self.id].id_parent)
+    }
+
+    /// Returns a cursor on the left child of the node, if any.
+    pub fn left(&self) -> Option<Self> {
+        self.at(self.tree.nodes[self.id].id_left)
+    }
+
+    /// Returns a cursor on the right child of the node, if any.
+    pub fn right(&self) -> Option<Self> {
+        self.at(self.tree.nodes[self.id].id_right)
+    }
+
+    /// Returns a cursor on the other child of the parent of the node, if any.
+    pub fn sibling(&self) -> Option<Self> {
+        self.at(self.tree.sibling(self.id))
+    }
+
+    /// A private function that returns a cursor on `id` in the same tree.
+    fn at(&self, id: Option<usize>) -> Option<Self> {
+        id.map(|id| Cursor {
+            tree: self.tree,
+            id,
+        })
+    }
+}
+
+/// A cursor on a node of a [`Tree`] that can edit the tree around it,
+/// created by [`Tree::cursor_mut`]. Moving the cursor changes its position
+/// in place and returns the id of the new node, or `None` without moving.
+pub struct CursorMut<'a, K> {
+    tree: &'a mut Tree<K>,
+    id: usize,
+}
+
+impl<K> CursorMut<'_, K> {
+    /// Returns the id of the node under the cursor.
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    /// Returns the key of the node under the cursor.
+    pub fn key(&self) -> &K {
+        self.tree.nodes[self.id].key()
+    }
+
+    /// Returns a mutable reference to the key of the node under the cursor.
+    pub fn key_mut(&mut self) -> &mut K {
+        self.tree.nodes[self.id]
+            .key
+            .as_mut()
+            .expect("Node id does not exist")
+    }
+
+    /// Returns the side the node hangs on below its parent, or `None` for the root.
+    pub fn side(&self) -> Option<Side> {
+        self.tree.side(self.id)
+    }
+
+    /// Returns a read-only cursor on the same node.
+    pub fn as_cursor(&self) -> Cursor<'_, K> {
+        Cursor {
+            tree: self.tree,
+            id: self.id,
+        }
+    }
+
+    /// Moves the cursor to the parent of the node.
+    pub fn parent(&mut self) -> Option<usize> {
+        self.move_to(self.tree.nodes[self.id].id_parent)
+    }
+
+    /// Moves the cursor to the left child of the node.
+    pub fn left(&mut self) -> Option<usize> {
+        self.move_to(self.tree.nodes[self.id].id_left)
+    }
+
+    /// Moves the cursor to the right child of the node.
+    pub fn right(&mut self) -> Option<usize> {
+        self.move_to(self.tree.nodes[self.id].id_right)
+    }
+
+    /// Moves the cursor to the other child of the parent of the node.
+    pub fn sibling(&mut self) -> Option<usize> {
+        self.move_to(self.tree.sibling(self.id))
+    }
+
+    /// Adds a left child with the specified `key` to the node and returns its id.
+    /// The cursor does not move.
+    pub fn insert_left(&mut self, key: K) -> Result<usize, TreeError> {
+        self.tree.try_add_node(self.id, key, true)
+    }
+
+    /// Adds a right child with the specified `key` to the node and returns its id.
+    /// The cursor does not move.
+    pub fn insert_right(&mut self, key: K) -> Result<usize, TreeError> {
+        self.tree.try_add_node(self.id, key, false)
+    }
+
+    /// Removes the subtree rooted at the node from the tree and returns it as
+    /// a new tree, whose ids follow the pre-order of the subtree starting from 0.
+    pub fn detach(self) -> Tree<K> {
+        let tree = self.tree;
+        let parent_id = tree.nodes[self.id].id_parent;
+        tree.replace_link(parent_id, self.id, None);
+
+        let mut detached = Tree::new();
+        // Each entry holds an old id and where its copy hangs in the new tree
+        let mut stack = vec![(self.id, None)];
+
+        while let Some((id, parent)) = stack.pop() {
+            let node = &tree.nodes[id];
+            let (left, right) = (node.id_left, node.id_right);
+            let key = tree.release(id);
+
+            let new_id = match parent {
+                Some((parent_id, is_left)) => detached.link_new_child(parent_id, key, is_left),
+                None => detached.add_root(key),
+            };
+            stack.extend(right.map(|right| (right, Some((new_id, false)))));
+            stack.extend(left.map(|left| (left, Some((new_id, true)))));
+        }
+
+        detached
+    }
+
+    /// A private function that moves the cursor to `id`, if any.
+    fn move_to(&mut self, id: Option<usize>) -> Option<usize> {
+        if let Some(id) = id {
+            self.id = id;
+        }
+        id
+    }
+}
+
+impl<K> Tree<K> {
+    /// Returns a read-only cursor on the node `id`, or `None` if it does not exist.
+    pub fn cursor(&self, id: usize) -> Option<Cursor<'_, K>> {
+        self.contains_id(id).then_some(Cursor { tree: self, id })
+    }
+
+    /// Returns a cursor that can edit the tree, starting on the node `id`,
+    /// or `None` if it does not exist.
+    pub fn cursor_mut(&mut self, id: usize) -> Option<CursorMut<'_, K>> {
+        if !self.contains_id(id) {
+            return None;
+        }
+        Some(CursorMut { tree: self, id })
+    }
+
+    /// A private function that returns the side of the node `id` below its parent.
+    fn side(&self, id: usize) -> Option<Side> {
+        let parent_id = self.nodes[id].id_parent?;
+        if self.nodes[parent_id].id_left == Some(id) {
+            Some(Side::Left)
+        } else {
+            Some(Side::Right)
+        }
+    }
+
+    /// A private function that returns the other child of the parent of `id`.
+    fn sibling(&self, id: usize) -> Option<usize> {
+        let parent = &self.nodes[self.nodes[id].id_parent?];
+        if parent.id_left == Some(id) {
+            parent.id_right
+        } else {
+            parent.id_left
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AvlTree, ReattachPolicy};
+
+    fn sample() -> Tree<u32> {
+        "[10, 5, 22, null, 7, 20]".parse().unwrap()
+    }
+
+    /// Checks that every child links back to its parent and the root has no parent.
+    fn assert_parent_links<K>(tree: &Tree<K>) {
+        if let Some(root) = tree.root {
+            assert_eq!(tree.nodes[root].id_parent, None);
+        }
+        for (id, node) in tree.nodes.iter().enumerate() {
+            if node.key.is_none() {
+                continue;
+            }
+            for child in node.id_left.into_iter().chain(node.id_right) {
+                assert_eq!(tree.nodes[child].id_parent, Some(id));
+            }
+        }
+    }
+
+    #[test]
+    fn test_cursor_navigation() {
+        let tree = sample();
+        let root = tree.cursor(0).unwrap();
+        assert_eq!(root.key(), &10);
+        assert_eq!(root.side(), None);
+        assert!(root.parent().is_none());
+        assert!(root.sibling().is_none());
+
+        let five = root.left().unwrap();
+        assert_eq!(five.side(), Some(Side::Left));
+        assert_eq!(five.sibling().map(|c| c.id()), Some(2));
+        assert!(five.left().is_none());
+
+        let seven = five.right().unwrap();
+        assert_eq!(seven.key(), &7);
+        assert_eq!(seven.side(), Some(Side::Right));
+        assert!(seven.sibling().is_none());
+        assert_eq!(seven.parent().unwrap().parent().unwrap().id(), 0);
+
+        assert!(tree.cursor(5).is_none());
+    }
+
+    #[test]
+    fn test_cursor_mut_edit() {
+        let mut tree = sample();
+        let mut cursor = tree.cursor_mut(0).unwrap();
+        assert_eq!(cursor.right(), Some(2));
+        assert_eq!(cursor.right(), None);
+        assert_eq!(cursor.id(), 2);
+
+        *cursor.key_mut() += 1;
+        assert_eq!(cursor.insert_right(30), Ok(5));
+        assert_eq!(cursor.insert_left(1), Err(TreeError::LeftOccupied(2)));
+        assert_eq!(cursor.sibling(), Some(1));
+        assert_eq!(cursor.as_cursor().side(), Some(Side::Left));
+        assert_eq!(cursor.parent(), Some(0));
+        assert_eq!(cursor.parent(), None);
+
+        assert_eq!(tree.nodes[2].key(), &23);
+        assert_eq!(tree.sum(), 95);
+        assert_parent_links(&tree);
+    }
+
+    #[test]
+    fn test_cursor_detach() {
+        let mut tree = sample();
+        tree.add_node(4, 21, false); // id 5
+        tree.add_node(2, 30, false); // id 6
+
+        let detached = tree.cursor_mut(2).unwrap().detach();
+        assert_eq!(
+            detached.to_level_order(),
+            [Some(22), Some(20), Some(30), None, Some(21)]
+        );
+        // Pre-order ids: 22, 20, 21, 30
+        assert_eq!(detached.nodes[2].key(), &21);
+        assert_eq!(detached.nodes[3].key(), &30);
+        assert_parent_links(&detached);
+
+        assert_eq!(tree.len(), 3);
+        assert_eq!(tree.nodes[0].id_right, None);
+        assert_parent_links(&tree);
+
+        let whole = tree.cursor_mut(0).unwrap().detach();
+        assert!(tree.is_empty());
+        assert_eq!(
+            whole.to_level_order(),
+            [Some(10), Some(5), None, None, Some(7)]
+        );
+    }
+
+    #[test]
+    fn test_parent_links_after_edits() {
+        let mut tree = sample();
+        tree.add_node(1, 3, true); // id 5
+        tree.remove_node(1, ReattachPolicy::PromoteRight);
+        assert_parent_links(&tree);
+        tree.remove_node(0, ReattachPolicy::PromoteLeft);
+        assert_parent_links(&tree);
+        tree.replace_child(tree.root().unwrap(), 1, true).unwrap();
+        assert_parent_links(&tree);
+
+        let mut avl = AvlTree::new();
+        for key in [50, 20, 80, 10, 30, 25, 27, 90, 85, 5, 1] {
+            avl.insert(key);
+        }
+        for key in [20, 50, 1] {
+            avl.remove(&key);
+        }
+        assert_parent_links(avl.tree());
+        assert_eq!(avl.check_invariants(), Ok(()));
+    }
+}
@@ -0,0 +1,296 @@
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Read, Write};
+
+use crate::Tree;
+
+/// The bytes every encoded tree starts with.
+const MAGIC: [u8; 4] = *b"HTRE";
+/// The version of the encoding written by [`Tree::write_to`].
+const VERSION: u8 = 1;
+
+/// The presence bits of the children of a node.
+const HAS_LEFT: u8 = 0b01;
+const HAS_RIGHT: u8 = 0b10;
+
+/// Keys that can be stored in the binary encoding of a [`Tree`].
+/// It is implemented for the fixed-width integer types, as little-endian bytes.
+pub trait KeyCodec: Sized {
+    /// Writes the key to `writer`.
+    fn write_key<W: Write>(&self, writer: &mut W) -> io::Result<()>;
+
+    /// Reads a key written by [`KeyCodec::write_key`] from `reader`.
+    fn read_key<R: Read>(reader: &mut R) -> io::Result<Self>;
+}
+
+macro_rules! impl_key_codec {
+    ($($t:ty),*) => {
+        $(
+            impl KeyCodec for $t {
+                fn write_key<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+                    writer.write_all(&self.to_le_bytes())
+                }
+
+                fn read_key<R: Read>(reader: &mut R) -> io::Result<Self> {
+                    let mut bytes = [0; std::mem::size_of::<$t>()];
+                    reader.read_exact(&mut bytes)?;
+                    Ok(<$t>::from_le_bytes(bytes))
+                }
+            }
+        )*
+    };
+}
+
+impl_key_codec!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+/// The error returned when a tree cannot be read by [`Tree::read_from`].
+#[derive(Debug)]
+pub enum DecodeError {
+    /// Reading failed, e.g. because the input ended too early.
+    Io(io::Error),
+    /// The input does not start with the magic bytes of the encoding.
+    BadMagic,
+    /// The input was written by an unknown version of the encoding.
+    UnsupportedVersion(u8),
+    /// The node at this position in pre-order has unknown bits in its flags.
+    InvalidFlags(usize),
+    /// The child presence bits describe a different number of nodes than the header.
+    CountMismatch,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::Io(err) => write!(f, "Failed to read the tree: {err}"),
+            DecodeError::BadMagic => write!(f, "Input is not an encoded tree"),
+            DecodeError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported encoding version {version}")
+            }
+            DecodeError::InvalidFlags(index) => write!(f, "Invalid flags for node {index}"),
+            DecodeError::CountMismatch => {
+                write!(f, "Node count does not match the shape of the tree")
+            }
+        }
+    }
+}
+
+impl Error for DecodeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            DecodeError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for DecodeError {
+    fn from(err: io::Error) -> Self {
+        DecodeError::Io(err)
+    }
+}
+
+impl<K: KeyCodec> Tree<K> {
+    /// Writes the tree to `writer` in a compact binary encoding: the magic bytes
+    /// `HTRE`, a version byte, the number of nodes as a little-endian `u64`, then
+    /// the nodes in pre-order, each as its key followed by a byte with the
+    /// presence bits of its left and right child. Node ids are not stored.
+    pub fn write_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_all(&MAGIC)?;
+        writer.write_all(&[VERSION])?;
+        writer.write_all(&(self.len() as u64).to_le_bytes())?;
+
+        let mut stack = Vec::from_iter(self.root);
+        while let Some(id) = stack.pop() {
+            let node = &self.nodes[id];
+            node.key().write_key(&mut writer)?;
+
+            let mut flags = 0;
+            if node.id_left.is_some() {
+                flags |= HAS_LEFT;
+            }
+            if node.id_right.is_some() {
+                flags |= HAS_RIGHT;
+            }
+            writer.write_all(&[flags])?;
+
+            stack.extend(node.id_right.into_iter().chain(node.id_left));
+        }
+
+        writer.flush()
+    }
+
+    /// Reads a tree written by [`Tree::write_to`]. The ids of the nodes follow
+    /// the pre-order of the tree, starting from 0 for the root.
+    ///
+    /// The input is validated while it is read: since every node is linked to the
+    /// slot left open by the presence bits of an earlier node, the result is always
+    /// a proper tree, and the number of nodes must match the header exactly.
+    pub fn read_from<R: Read>(mut reader: R) -> Result<Self, DecodeError> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if magic != MAGIC {
+            return Err(DecodeError::BadMagic);
+        }
+
+        let version = read_byte(&mut reader)?;
+        if version != VERSION {
+            return Err(DecodeError::UnsupportedVersion(version));
+        }
+
+        let mut count = [0; 8];
+        reader.read_exact(&mut count)?;
+        let count = u64::from_le_bytes(count);
+
+        let mut tree = Tree::new();
+        // The open child slots, the next node fills the last one;
+        // `None` stands for the root
+        let mut slots: Vec<Option<(usize, bool)>> = Vec::new();
+        if count > 0 {
+            slots.push(None);
+        }
+
+        for index in 0..count {
+            let slot = slots.pop().ok_or(DecodeError::CountMismatch)?;
+            let key = K::read_key(&mut reader)?;
+            let flags = read_byte(&mut reader)?;
+            if flags & !(HAS_LEFT | HAS_RIGHT) != 0 {
+                return Err(DecodeError::InvalidFlags(index as usize));
+            }
+
+            let id = match slot {
+                Some((parent_id, is_left)) => tree.link_new_child(parent_id, key, is_left),
+                None => tree.add_root(key),
+            };
+            if flags & HAS_RIGHT != 0 {
+                slots.push(Some((id, false)));
+            }
+            if flags & HAS_LEFT != 0 {
+                slots.push(Some((id, true)));
+            }
+        }
+
+        if !slots.is_empty() {
+            return Err(DecodeError::CountMismatch);
+        }
+
+        Ok(tree)
+    }
+}
+
+/// Reads a single byte from `reader`.
+fn read_byte<R: Read>(reader: &mut R) -> io::Result<u8> {
+    let mut byte = [0];
+    reader.read_exact(&mut byte)?;
+    Ok(byte[0])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode<K: KeyCodec>(tree: &Tree<K>) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        tree.write_to(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_write_to() {
+        let tree: Tree<u16> = "[10, 5, 22, null, 7]".parse().unwrap();
+
+        assert_eq!(
+            encode(&tree),
+            [
+                b'H', b'T', b'R', b'E', 1, // header
+                4, 0, 0, 0, 0, 0, 0, 0, // count
+                10, 0, 0b11, // 10 with both children
+                5, 0, 0b10, // 5 with a right child
+                7, 0, 0, // 7
+                22, 0, 0, // 22
+            ]
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut tree: Tree<i64> = "[-10, 9, 20, null, null, 15, 7, 1]".parse().unwrap();
+        tree.remove_subtree(1);
+        tree.add_node(4, i64::MIN, false); // reuses id 1
+
+        let read = Tree::<i64>::read_from(encode(&tree).as_slice()).unwrap();
+        assert!(read.is_identical(&tree));
+        assert_eq!(read.to_level_order(), tree.to_level_order());
+
+        let empty = Tree::<u8>::read_from(encode(&Tree::<u8>::new()).as_slice()).unwrap();
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn test_round_trip_deep_chain() {
+        let mut tree = Tree::with_root(0u32);
+        let mut last = 0;
+        for key in 1..100_000 {
+            last = tree.add_node(last, key, key % 3 == 0);
+        }
+
+        let read = Tree::<u32>::read_from(encode(&tree).as_slice()).unwrap();
+        assert_eq!(read.len(), 100_000);
+        assert_eq!(read.to_level_order(), tree.to_level_order());
+    }
+
+    #[test]
+    fn test_read_from_invalid() {
+        let tree: Tree<u8> = "[1, 2, 3]".parse().unwrap();
+        let bytes = encode(&tree);
+
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] = b'X';
+        assert!(matches!(
+            Tree::<u8>::read_from(bad_magic.as_slice()),
+            Err(DecodeError::BadMagic)
+        ));
+
+        let mut bad_version = bytes.clone();
+        bad_version[4] = 2;
+        assert!(matches!(
+            Tree::<u8>::read_from(bad_version.as_slice()),
+            Err(DecodeError::UnsupportedVersion(2))
+        ));
+
+        let mut bad_flags = bytes.clone();
+        bad_flags[16] = 0b100;
+        assert!(matches!(
+            Tree::<u8>::read_from(bad_flags.as_slice()),
+            Err(DecodeError::InvalidFlags(1))
+        ));
+
+        let truncated = &bytes[..bytes.len() - 1];
+        assert!(matches!(
+            Tree::<u8>::read_from(truncated),
+            Err(DecodeError::Io(err)) if err.kind() == io::ErrorKind::UnexpectedEof
+        ));
+    }
+
+    #[test]
+    fn test_read_from_count_mismatch() {
+        let tree: Tree<u8> = "[1, 2, 3]".parse().unwrap();
+
+        // The header claims more nodes than the presence bits allow
+        let mut too_many = encode(&tree);
+        too_many[5] = 4;
+        too_many.extend([4, 0]);
+        assert!(matches!(
+            Tree::<u8>::read_from(too_many.as_slice()),
+            Err(DecodeError::CountMismatch)
+        ));
+
+        // The presence bits announce children that never come
+        let mut too_few = encode(&tree);
+        too_few[5] = 2;
+        too_few.truncate(too_few.len() - 2);
+        assert!(matches!(
+            Tree::<u8>::read_from(too_few.as_slice()),
+            Err(DecodeError::CountMismatch)
+        ));
+    }
+}
@@ -0,0 +1,165 @@
+use crate::Tree;
+
+/// An index answering lowest common ancestor queries on a [`Tree`] with binary lifting.
+///
+/// Building the index takes O(n log n) time and space, then every query takes
+/// O(log n). The index is a snapshot: it has to be rebuilt after the tree changes.
+pub struct LcaIndex {
+    /// The depth of each id, with the root at depth 0, or `None` for free slots.
+    depth: Vec<Option<usize>>,
+    /// `up[k][id]` is the ancestor 2^k levels above `id`, or the root if there is none.
+    up: Vec<Vec<usize>>,
+}
+
+impl LcaIndex {
+    /// Builds the index for the nodes currently in `tree`.
+    pub fn new<K>(tree: &Tree<K>) -> Self {
+        let n = tree.nodes.len();
+        let mut depth: Vec<Option<usize>> = vec![None; n];
+        let mut parent = vec![0; n];
+
+        // Visit the tree top-down, so every parent is done before its children
+        let mut stack = Vec::from_iter(tree.root);
+        if let Some(root) = tree.root {
+            depth[root] = Some(0);
+            parent[root] = root;
+        }
+        while let Some(id) = stack.pop() {
+            let node = &tree.nodes[id];
+            for child in node.id_left.into_iter().chain(node.id_right) {
+                depth[child] = depth[id].map(|depth| depth + 1);
+                parent[child] = id;
+                stack.push(child);
+            }
+        }
+
+        let max_depth = depth.iter().flatten().copied().max().unwrap_or(0);
+        let levels = (usize::BITS - max_depth.leading_zeros()).max(1) as usize;
+
+        let mut up = Vec::with_capacity(levels);
+        up.push(parent);
+        for k in 1..levels {
+            let previous = &up[k - 1];
+            let next = previous
+                .iter()
+                .map(|&ancestor| previous[ancestor])
+                .collect();
+            up.push(next);
+        }
+
+        Self { depth, up }
+    }
+
+    /// Returns the depth of the node `id`, where the root has depth 0,
+    /// or `None` if the node was not in the tree.
+    pub fn depth(&self, id: usize) -> Option<usize> {
+        self.depth.get(id).copied().flatten()
+    }
+
+    /// Returns the lowest common ancestor of the nodes `a` and `b`,
+    /// or `None` if either of them was not in the tree.
+    pub fn lca(&self, a: usize, b: usize) -> Option<usize> {
+        let (depth_a, depth_b) = (self.depth(a)?, self.depth(b)?);
+        let (mut deep, mut shallow) = if depth_a >= depth_b { (a, b) } else { (b, a) };
+
+        // Lift the deeper node to the depth of the other one
+        let diff = depth_a.abs_diff(depth_b);
+        for (k, up) in self.up.iter().enumerate() {
+            if diff >> k & 1 == 1 {
+                deep = up[deep];
+            }
+        }
+
+        if deep == shallow {
+            return Some(deep);
+        }
+
+        // Lift both nodes as long as they stay below the common ancestor
+        for up in self.up.iter().rev() {
+            if up[deep] != up[shallow] {
+                deep = up[deep];
+                shallow = up[shallow];
+            }
+        }
+
+        Some(self.up[0][deep])
+    }
+
+    /// Returns the number of edges on the path between the nodes `a` and `b`,
+    /// or `None` if either of them was not in the tree.
+    pub fn distance(&self, a: usize, b: usize) -> Option<usize> {
+        let lca = self.lca(a, b)?;
+        let depth_lca = self.depth(lca)?;
+        Some(self.depth(a)? + self.depth(b)? - 2 * depth_lca)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ReattachPolicy;
+
+    fn org_chart() -> Tree<&'static str> {
+        let mut tree = Tree::with_root("ceo");
+        let cto = tree.add_node(0, "cto", true); // id 1
+        let cfo = tree.add_node(0, "cfo", false); // id 2
+        let dev = tree.add_node(cto, "dev lead", true); // id 3
+        tree.add_node(cto, "ops lead", false); // id 4
+        tree.add_node(dev, "dev 1", true); // id 5
+        tree.add_node(dev, "dev 2", false); // id 6
+        tree.add_node(cfo, "accountant", true); // id 7
+        tree
+    }
+
+    #[test]
+    fn test_lca() {
+        let index = LcaIndex::new(&org_chart());
+
+        assert_eq!(index.lca(5, 6), Some(3));
+        assert_eq!(index.lca(5, 4), Some(1));
+        assert_eq!(index.lca(6, 7), Some(0));
+        assert_eq!(index.lca(3, 5), Some(3));
+        assert_eq!(index.lca(0, 0), Some(0));
+        assert_eq!(index.lca(2, 2), Some(2));
+        assert_eq!(index.lca(5, 42), None);
+    }
+
+    #[test]
+    fn test_depth_and_distance() {
+        let index = LcaIndex::new(&org_chart());
+
+        assert_eq!(index.depth(0), Some(0));
+        assert_eq!(index.depth(6), Some(3));
+        assert_eq!(index.distance(5, 6), Some(2));
+        assert_eq!(index.distance(5, 7), Some(5));
+        assert_eq!(index.distance(4, 4), Some(0));
+        assert_eq!(index.distance(0, 6), Some(3));
+    }
+
+    #[test]
+    fn test_removed_nodes() {
+        let mut tree = org_chart();
+        tree.remove_node(3, ReattachPolicy::PromoteLeft);
+        let index = LcaIndex::new(&tree);
+
+        assert_eq!(index.depth(3), None);
+        assert_eq!(index.lca(3, 4), None);
+        assert_eq!(index.lca(5, 6), Some(5));
+        assert_eq!(index.distance(6, 4), Some(3));
+    }
+
+    #[test]
+    fn test_deep_chain() {
+        let mut tree = Tree::with_root(0);
+        let mut last = 0;
+        for key in 1..100_000 {
+            last = tree.add_node(last, key, key % 2 == 0);
+        }
+        let branch = tree.add_node(50_000, -1, true);
+        let index = LcaIndex::new(&tree);
+
+        assert_eq!(index.depth(last), Some(99_999));
+        assert_eq!(index.lca(last, branch), Some(50_000));
+        assert_eq!(index.distance(last, branch), Some(50_000));
+    }
+}
@@ -0,0 +1,225 @@
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::Tree;
+
+/// The error returned when a tree cannot be built from its level-order representation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LevelOrderError {
+    /// The text is not a list of comma-separated entries between square brackets.
+    Syntax,
+    /// The entry at this index is neither `null` nor a valid key.
+    InvalidKey(usize),
+    /// The entry at this index has no parent node to hang from.
+    Orphan(usize),
+}
+
+impl fmt::Display for LevelOrderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LevelOrderError::Syntax => write!(f, "Expected a list like [10, 5, null, 7]"),
+            LevelOrderError::InvalidKey(index) => write!(f, "Invalid key at index {index}"),
+            LevelOrderError::Orphan(index) => write!(f, "Entry at index {index} has no parent"),
+        }
+    }
+}
+
+impl Error for LevelOrderError {}
+
+impl<K: Clone> Tree<K> {
+    /// Builds a tree from its level-order representation, where `None` marks a
+    /// missing child and the children of missing nodes are not listed, e.g.
+    /// `[Some(10), Some(5), None, Some(7)]` is a root 10 with a left child 5,
+    /// which has a left child 7. The ids of the nodes follow the same order,
+    /// starting from 0 for the root.
+    pub fn from_level_order(entries: &[Option<K>]) -> Result<Self, LevelOrderError> {
+        let mut tree = Tree::new();
+        let mut entries = entries.iter().cloned().enumerate();
+
+        if let Some((_, Some(key))) = entries.next() {
+            tree.add_root(key);
+        }
+
+        // Every node takes the next two entries as its left and right child
+        let mut parents = VecDeque::from_iter(tree.root);
+        while let Some((index, entry)) = entries.next() {
+            let Some(parent_id) = parents.pop_front() else {
+                if entry.is_some() {
+                    return Err(LevelOrderError::Orphan(index));
+                }
+                continue;
+            };
+
+            if let Some(key) = entry {
+                parents.push_back(tree.add_node(parent_id, key, true));
+            }
+            if let Some((_, Some(key))) = entries.next() {
+                parents.push_back(tree.add_node(parent_id, key, false));
+            }
+        }
+
+        Ok(tree)
+    }
+
+    /// Returns the level-order representation of the tree, as accepted by
+    /// [`Tree::from_level_order`], without trailing `None` entries.
+    pub fn to_level_order(&self) -> Vec<Option<K>> {
+        let mut entries = Vec::new();
+        let mut queue = VecDeque::from_iter(self.root.map(Some));
+
+        while let Some(node_id) = queue.pop_front() {
+            match node_id {
+                Some(id) => {
+                    let node = &self.nodes[id];
+                    entries.push(Some(node.key().clone()));
+                    queue.push_back(node.id_left);
+                    queue.push_back(node.id_right);
+                }
+                None => entries.push(None),
+            }
+        }
+
+        while entries.last().is_some_and(Option::is_none) {
+            entries.pop();
+        }
+
+        entries
+    }
+}
+
+/// Parses a tree from its level-order representation written as text,
+/// e.g. `"[10, 5, null, 7]"`. See [`Tree::from_level_order`].
+impl<K: Clone + FromStr> FromStr for Tree<K> {
+    type Err = LevelOrderError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let list = s
+            .trim()
+            .strip_prefix('[')
+            .and_then(|s| s.strip_suffix(']'))
+            .ok_or(LevelOrderError::Syntax)?
+            .trim();
+
+        if list.is_empty() {
+            return Ok(Tree::new());
+        }
+
+        let entries = list
+            .split(',')
+            .map(str::trim)
+            .enumerate()
+            .map(|(index, entry)| match entry {
+                "null" => Ok(None),
+                entry => entry
+                    .parse()
+                    .map(Some)
+                    .map_err(|_| LevelOrderError::InvalidKey(index)),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Tree::from_level_order(&entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_level_order() {
+        let tree = Tree::from_level_order(&[Some(10), Some(5), None, Some(7)]).unwrap();
+
+        assert_eq!(tree.len(), 3);
+        assert_eq!(tree.nodes[0].id_left, Some(1));
+        assert_eq!(tree.nodes[0].id_right, None);
+        assert_eq!(tree.nodes[1].id_left, Some(2));
+        assert_eq!(tree.sum(), 22);
+    }
+
+    #[test]
+    fn test_from_level_order_empty() {
+        assert!(Tree::<u32>::from_level_order(&[]).unwrap().is_empty());
+        assert!(Tree::<u32>::from_level_order(&[None]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_from_level_order_orphan() {
+        assert_eq!(
+            Tree::from_level_order(&[None, Some(1)]).err(),
+            Some(LevelOrderError::Orphan(1))
+        );
+        assert_eq!(
+            Tree::from_level_order(&[Some(1), None, None, Some(2)]).err(),
+            Some(LevelOrderError::Orphan(3))
+        );
+        assert_eq!(
+            Tree::from_level_order(&[Some(1), None, None, None, Some(2)]).err(),
+            Some(LevelOrderError::Orphan(4))
+        );
+        assert!(Tree::from_level_order(&[Some(1), None, None, None]).is_ok());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut tree = Tree::with_root(10);
+        tree.add_node(0, 5, true);
+        tree.add_node(0, 15, false);
+        tree.add_node(1, 7, false);
+        tree.add_node(2, 20, false);
+        tree.add_node(3, 6, true);
+
+        let entries = tree.to_level_order();
+        assert_eq!(
+            entries,
+            [
+                Some(10),
+                Some(5),
+                Some(15),
+                None,
+                Some(7),
+                None,
+                Some(20),
+                Some(6)
+            ]
+        );
+        assert_eq!(
+            Tree::from_level_order(&entries).unwrap().to_level_order(),
+            entries
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        let tree: Tree<u32> = "[10, 5, 15, 3, 7, 12, 20]".parse().unwrap();
+        assert_eq!(tree.max_path_sum(), 57);
+        assert!(tree.is_bst());
+
+        let tree: Tree<i32> = " [ -10,9,20,null,null,15,7 ] ".parse().unwrap();
+        assert_eq!(tree.max_path().map(|(sum, _)| sum), Some(42));
+
+        let tree: Tree<u32> = "[]".parse().unwrap();
+        assert!(tree.is_empty());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            "10, 5".parse::<Tree<u32>>().err(),
+            Some(LevelOrderError::Syntax)
+        );
+        assert_eq!(
+            "[10, -5]".parse::<Tree<u32>>().err(),
+            Some(LevelOrderError::InvalidKey(1))
+        );
+        assert_eq!(
+            "[10, , 5]".parse::<Tree<u32>>().err(),
+            Some(LevelOrderError::InvalidKey(1))
+        );
+        assert_eq!(
+            "[null, 5]".parse::<Tree<u32>>().err(),
+            Some(LevelOrderError::Orphan(1))
+        );
+    }
+}
@@ -1,76 +1,451 @@
-pub struct Node {
-    key: u32,
+use std::error::Error;
+use std::fmt;
+use std::ops::Add;
+
+mod aggregate;
+mod avl;
+mod balance;
+mod bst;
+mod cursor;
+mod encoding;
+mod lca;
+mod level_order;
+mod metrics;
+mod parallel;
+mod path_sum;
+mod render;
+mod reroot;
+mod restructure;
+mod subtree;
+
+pub use aggregate::AggregateIndex;
+pub use avl::{AvlTree, AvlViolation};
+pub use bst::Range;
+pub use cursor::{Cursor, CursorMut, Side};
+pub use encoding::{DecodeError, KeyCodec};
+pub use lca::LcaIndex;
+pub use level_order::LevelOrderError;
+pub use metrics::TreeMetrics;
+pub use subtree::Orientation;
+
+/// Integer key types the path aggregates of a [`Tree`] can work with.
+pub trait Numeric: Copy + Ord + Add<Output = Self> {
+    /// The additive identity.
+    const ZERO: Self;
+
+    /// Adds `rhs`, returning `None` on overflow.
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+
+    /// Subtracts `rhs`, returning `None` on overflow.
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+
+    /// Adds `rhs`, wrapping around at the bounds of the type.
+    fn wrapping_add(self, rhs: Self) -> Self;
+
+    /// Subtracts `rhs`, wrapping around at the bounds of the type.
+    fn wrapping_sub(self, rhs: Self) -> Self;
+}
+
+macro_rules! impl_numeric {
+    ($($t:ty),*) => {
+        $(
+            impl Numeric for $t {
+                const ZERO: Self = 0;
+
+                fn checked_add(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_add(self, rhs)
+                }
+
+                fn checked_sub(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_sub(self, rhs)
+                }
+
+                fn wrapping_add(self, rhs: Self) -> Self {
+                    <$t>::wrapping_add(self, rhs)
+                }
+
+                fn wrapping_sub(self, rhs: Self) -> Self {
+                    <$t>::wrapping_sub(self, rhs)
+                }
+            }
+        )*
+    };
+}
+
+impl_numeric!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+/// The error returned by the fallible operations of a [`Tree`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TreeError {
+    /// The parent node id does not exist.
+    UnknownParent(usize),
+    /// The parent node has the left child already set.
+    LeftOccupied(usize),
+    /// The parent node has the right child already set.
+    RightOccupied(usize),
+}
+
+impl fmt::Display for TreeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TreeError::UnknownParent(_) => write!(f, "Parent node id does not exist"),
+            TreeError::LeftOccupied(_) => write!(f, "Parent node has the left child already set"),
+            TreeError::RightOccupied(_) => {
+                write!(f, "Parent node has the right child already set")
+            }
+        }
+    }
+}
+
+impl Error for TreeError {}
+
+pub struct Node<K> {
+    // `None` marks a slot that is on the free list
+    key: Option<K>,
     id_left: Option<usize>,
     id_right: Option<usize>,
+    id_parent: Option<usize>,
+    generation: u32,
 }
 
-impl Node {
-    fn new(key: u32) -> Self {
+impl<K> Node<K> {
+    fn new(key: K) -> Self {
         Self {
-            key,
+            key: Some(key),
             id_left: None,
             id_right: None,
+            id_parent: None,
+            generation: 0,
         }
     }
+
+    fn key(&self) -> &K {
+        self.key.as_ref().expect("Node id does not exist")
+    }
+}
+
+/// A generation-checked reference to a node of a [`Tree`].
+///
+/// Node ids are recycled once a node is removed, so a plain id kept around
+/// may end up addressing a node added later. A handle remembers the generation
+/// of the slot it was taken from and stops resolving once that node is removed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct NodeHandle {
+    id: usize,
+    generation: u32,
+}
+
+impl NodeHandle {
+    /// Returns the id the handle was taken from.
+    pub fn id(&self) -> usize {
+        self.id
+    }
+}
+
+/// Decides what happens to the children of a node removed with [`Tree::remove_node`].
+///
+/// A node with a single child is always replaced by that child. The policy only
+/// matters when both children are present; in both cases the in-order sequence
+/// of the remaining keys is preserved, so a BST stays a BST.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReattachPolicy {
+    /// The left child takes the place of the removed node and the right subtree
+    /// becomes the right child of the rightmost node of the left subtree.
+    PromoteLeft,
+    /// The right child takes the place of the removed node and the left subtree
+    /// becomes the left child of the leftmost node of the right subtree.
+    PromoteRight,
+}
+
+pub struct Tree<K = u32> {
+    nodes: Vec<Node<K>>,
+    free: Vec<usize>,
+    root: Option<usize>,
 }
 
-pub struct Tree {
-    nodes: Vec<Node>,
+impl<K> Default for Tree<K> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
-impl Tree {
-    pub fn with_root(key: u32) -> Self {
+impl<K> Tree<K> {
+    /// Creates an empty tree. Use [`Tree::add_root`] to give it a root.
+    pub fn new() -> Self {
         Self {
-            nodes: vec![Node::new(key)],
+            nodes: Vec::new(),
+            free: Vec::new(),
+            root: None,
+        }
+    }
+
+    pub fn with_root(key: K) -> Self {
+        let mut tree = Self::new();
+        tree.add_root(key);
+        tree
+    }
+
+    /// Adds the root node with the specified `key` to an empty tree and returns its id.
+    ///
+    /// # Panics
+    /// Panics if the tree already has a root.
+    pub fn add_root(&mut self, key: K) -> usize {
+        assert!(self.root.is_none(), "Tree has the root already set");
+        let root_id = self.alloc(key);
+        self.root = Some(root_id);
+        root_id
+    }
+
+    /// Returns the id of the root, or `None` if the tree is empty.
+    pub fn root(&self) -> Option<usize> {
+        self.root
+    }
+
+    /// Returns the number of nodes in the tree.
+    pub fn len(&self) -> usize {
+        self.nodes.len() - self.free.len()
+    }
+
+    /// Returns `true` if the tree has no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Returns `true` if `id` addresses a node currently in the tree.
+    pub fn contains_id(&self, id: usize) -> bool {
+        id < self.nodes.len() && self.nodes[id].key.is_some()
+    }
+
+    /// Returns a generation-checked handle to the node `id`,
+    /// or `None` if there is no such node.
+    pub fn handle(&self, id: usize) -> Option<NodeHandle> {
+        if !self.contains_id(id) {
+            return None;
+        }
+
+        Some(NodeHandle {
+            id,
+            generation: self.nodes[id].generation,
+        })
+    }
+
+    /// Returns the id addressed by `handle`, or `None` if the node it was
+    /// taken from has been removed in the meantime.
+    pub fn resolve(&self, handle: NodeHandle) -> Option<usize> {
+        if self.contains_id(handle.id) && self.nodes[handle.id].generation == handle.generation {
+            Some(handle.id)
+        } else {
+            None
         }
     }
 
     /// Adds a child to the node with `parent_id` and returns the id of the new node.
     /// The new node has the specified `key`. The new node is the left  child of the  
     /// node `parent_id` iff `is_left` is `true`, the right child otherwise.
+    /// Ids of removed nodes are reused before the arena grows.
     ///
     /// # Panics
     /// Panics if the `parent_id` does not exist, or if the node `parent_id` has  
     /// the child already set.
-    pub fn add_node(&mut self, parent_id: usize, key: u32, is_left: bool) -> usize {
-        assert!(
-            parent_id < self.nodes.len(),
-            "Parent node id does not exist"
-        );
-        if is_left {
-            assert!(
-                self.nodes[parent_id].id_left.is_none(),
-                "Parent node has the left child already set"
-            );
-        } else {
-            assert!(
-                self.nodes[parent_id].id_right.is_none(),
-                "Parent node has the right child already set"
-            );
+    pub fn add_node(&mut self, parent_id: usize, key: K, is_left: bool) -> usize {
+        self.try_add_node(parent_id, key, is_left)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Same as [`Tree::add_node`], but returns an error instead of panicking
+    /// if the `parent_id` does not exist or the child is already set.
+    pub fn try_add_node(
+        &mut self,
+        parent_id: usize,
+        key: K,
+        is_left: bool,
+    ) -> Result<usize, TreeError> {
+        self.check_free_slot(parent_id, is_left)?;
+        Ok(self.link_new_child(parent_id, key, is_left))
+    }
+
+    /// A private function that checks that the node `parent_id` exists
+    /// and that its child selected by `is_left` is not set.
+    fn check_free_slot(&self, parent_id: usize, is_left: bool) -> Result<(), TreeError> {
+        if !self.contains_id(parent_id) {
+            return Err(TreeError::UnknownParent(parent_id));
         }
+        if is_left && self.nodes[parent_id].id_left.is_some() {
+            return Err(TreeError::LeftOccupied(parent_id));
+        }
+        if !is_left && self.nodes[parent_id].id_right.is_some() {
+            return Err(TreeError::RightOccupied(parent_id));
+        }
+        Ok(())
+    }
 
-        let child_id = self.nodes.len();
-        self.nodes.push(Node::new(key));
+    /// Sets a new child with the specified `key` on the node `parent_id` and
+    /// returns the id of the new node, like [`Tree::add_node`]. If the child is
+    /// already set, the whole subtree rooted at it is removed first.
+    pub fn replace_child(
+        &mut self,
+        parent_id: usize,
+        key: K,
+        is_left: bool,
+    ) -> Result<usize, TreeError> {
+        if !self.contains_id(parent_id) {
+            return Err(TreeError::UnknownParent(parent_id));
+        }
 
-        let child = if is_left {
-            &mut self.nodes[parent_id].id_left
+        let parent = &self.nodes[parent_id];
+        let old_child = if is_left {
+            parent.id_left
         } else {
-            &mut self.nodes[parent_id].id_right
+            parent.id_right
         };
+        if let Some(old_child) = old_child {
+            self.remove_subtree(old_child);
+        }
 
-        *child = Some(child_id);
+        Ok(self.link_new_child(parent_id, key, is_left))
+    }
 
+    /// A private function that stores a new node with the specified `key` in the
+    /// empty child slot of `parent_id` selected by `is_left`, and returns its id.
+    fn link_new_child(&mut self, parent_id: usize, key: K, is_left: bool) -> usize {
+        let child_id = self.alloc(key);
+        self.set_child(parent_id, is_left, Some(child_id));
         child_id
     }
 
+    /// Removes the node `id` together with all its descendants and
+    /// returns the number of removed nodes. Removing the root empties the tree.
+    ///
+    /// # Panics
+    /// Panics if the node `id` does not exist.
+    pub fn remove_subtree(&mut self, id: usize) -> usize {
+        assert!(self.contains_id(id), "Node id does not exist");
+
+        let parent_id = self.nodes[id].id_parent;
+        self.replace_link(parent_id, id, None);
+
+        let mut removed = 0;
+        let mut stack = vec![id];
+
+        while let Some(id) = stack.pop() {
+            let node = &self.nodes[id];
+            stack.extend(node.id_left.into_iter().chain(node.id_right));
+            self.release(id);
+            removed += 1;
+        }
+
+        removed
+    }
+
+    /// Removes the single node `id` and returns its key. The children of the
+    /// removed node are re-attached to its parent according to `policy`.
+    ///
+    /// # Panics
+    /// Panics if the node `id` does not exist.
+    pub fn remove_node(&mut self, id: usize, policy: ReattachPolicy) -> K {
+        assert!(self.contains_id(id), "Node id does not exist");
+
+        let node = &self.nodes[id];
+        let replacement = match (node.id_left, node.id_right) {
+            (Some(left), Some(right)) => match policy {
+                ReattachPolicy::PromoteLeft => {
+                    let mut last = left;
+                    while let Some(next) = self.nodes[last].id_right {
+                        last = next;
+                    }
+                    self.set_child(last, false, Some(right));
+                    Some(left)
+                }
+                ReattachPolicy::PromoteRight => {
+                    let mut last = right;
+                    while let Some(next) = self.nodes[last].id_left {
+                        last = next;
+                    }
+                    self.set_child(last, true, Some(left));
+                    Some(right)
+                }
+            },
+            (left, right) => left.or(right),
+        };
+
+        let parent_id = self.nodes[id].id_parent;
+        self.replace_link(parent_id, id, replacement);
+
+        self.release(id)
+    }
+
+    /// A private function that stores a new node with the specified `key`,
+    /// reusing a free slot when there is one, and returns its id.
+    fn alloc(&mut self, key: K) -> usize {
+        if let Some(id) = self.free.pop() {
+            self.nodes[id].key = Some(key);
+            return id;
+        }
+
+        self.nodes.push(Node::new(key));
+        self.nodes.len() - 1
+    }
+
+    /// A private function that puts the slot `id` on the free list and returns
+    /// the key it held. Bumping the generation invalidates every handle taken so far.
+    fn release(&mut self, id: usize) -> K {
+        let node = &mut self.nodes[id];
+        let key = node.key.take().expect("Node id does not exist");
+        node.id_left = None;
+        node.id_right = None;
+        node.id_parent = None;
+        node.generation = node.generation.wrapping_add(1);
+        self.free.push(id);
+        key
+    }
+
+    /// A private function that sets the child of `parent_id` selected by `is_left`
+    /// to `child`, keeping the parent link of the child in sync.
+    fn set_child(&mut self, parent_id: usize, is_left: bool, child: Option<usize>) {
+        let parent = &mut self.nodes[parent_id];
+        if is_left {
+            parent.id_left = child;
+        } else {
+            parent.id_right = child;
+        }
+
+        if let Some(child) = child {
+            self.nodes[child].id_parent = Some(parent_id);
+        }
+    }
+
+    /// A private function that makes `new_child` take the place of `old_child`
+    /// below `parent_id`, or at the root when `parent_id` is `None`.
+    fn replace_link(
+        &mut self,
+        parent_id: Option<usize>,
+        old_child: usize,
+        new_child: Option<usize>,
+    ) {
+        match parent_id {
+            Some(parent_id) => {
+                let is_left = self.nodes[parent_id].id_left == Some(old_child);
+                self.set_child(parent_id, is_left, new_child);
+            }
+            None => {
+                self.root = new_child;
+                if let Some(new_child) = new_child {
+                    self.nodes[new_child].id_parent = None;
+                }
+            }
+        }
+    }
+}
+
+impl<K: Copy + Default + Add<Output = K>> Tree<K> {
     /// Returns the sum of all the keys in the tree
-    pub fn sum(&self) -> u32 {
-        self.rec_sum(Some(0))
+    pub fn sum(&self) -> K {
+        self.rec_sum(self.root)
     }
 
     /// A private recursive function that computes the sum of
     /// nodes in the subtree rooted at `node_id`.
-    fn rec_sum(&self, node_id: Option<usize>) -> u32 {
+    fn rec_sum(&self, node_id: Option<usize>) -> K {
         if let Some(id) = node_id {
             assert!(id < self.nodes.len(), "Node id is out of range");
             let node = &self.nodes[id];
@@ -78,45 +453,131 @@ impl Tree {
             let sum_left = self.rec_sum(node.id_left);
             let sum_right = self.rec_sum(node.id_right);
 
-            return sum_left + sum_right + node.key;
+            return sum_left + sum_right + *node.key();
         }
 
-        0
+        K::default()
     }
+}
 
+impl<K: Ord> Tree<K> {
     /// Checks if the tree satisfies the Binary Search Tree (BST) property.
     /// The tree is a BST if, for every node, the values in its left subtree
     /// are smaller than or equal to the node's key, and the values in its right subtree
     /// are greater.
     pub fn is_bst(&self) -> bool {
-        self.rec_is_bst(Some(0), u32::MIN, u32::MAX)
+        self.is_bst_with(DuplicatePolicy::Left)
+    }
+
+    /// Checks if the tree satisfies the BST property, placing keys equal to
+    /// the key of an ancestor as allowed by `policy`.
+    pub fn is_bst_with(&self, policy: DuplicatePolicy) -> bool {
+        self.bst_violation(policy).is_none()
+    }
+
+    /// Returns the first node, in pre-order, whose key breaks the BST property
+    /// under `policy`, together with the ancestor bound it broke.
+    /// Returns `None` if the tree is a BST.
+    pub fn bst_violation(&self, policy: DuplicatePolicy) -> Option<BstViolation<'_, K>> {
+        self.rec_bst_violation(self.root, None, None, policy)
     }
 
     /// A private recursive function that checks if the subtree rooted at `node_id`
-    /// satisfies the BST property. Each node's value must lie between `min` and `max`.
-    fn rec_is_bst(&self, node_id: Option<usize>, min: u32, max: u32) -> bool {
-        if let Some(id) = node_id {
-            assert!(id < self.nodes.len(), "Node id is out of range");
-            let node = &self.nodes[id];
+    /// satisfies the BST property. Each node's value must lie between the keys of
+    /// the ancestors `min` and `max`, where `None` leaves that side unbounded.
+    fn rec_bst_violation(
+        &self,
+        node_id: Option<usize>,
+        min: Option<usize>,
+        max: Option<usize>,
+        policy: DuplicatePolicy,
+    ) -> Option<BstViolation<'_, K>> {
+        let id = node_id?;
+        assert!(id < self.nodes.len(), "Node id is out of range");
+        let node = &self.nodes[id];
+        let key = node.key();
 
-            // If the node's key violates the BST property, return false
-            if !(node.key > min && node.key <= max) {
-                return false;
+        // If the node's key violates the BST property, report the broken bound
+        if let Some(min) = min {
+            let bound = self.nodes[min].key();
+            if key < bound || (key == bound && !policy.allows_right()) {
+                return Some(BstViolation {
+                    id,
+                    ancestor: min,
+                    bound: BrokenBound::Lower(bound),
+                });
+            }
+        }
+        if let Some(max) = max {
+            let bound = self.nodes[max].key();
+            if key > bound || (key == bound && !policy.allows_left()) {
+                return Some(BstViolation {
+                    id,
+                    ancestor: max,
+                    bound: BrokenBound::Upper(bound),
+                });
             }
-
-            // Recursively check the left and right subtrees, updating bounds
-            return self.rec_is_bst(node.id_left, min, node.key)
-                && self.rec_is_bst(node.id_right, node.key, max);
         }
 
-        true // Return true if the node_id is None (leaf node's child)
+        // Recursively check the left and right subtrees, updating bounds
+        self.rec_bst_violation(node.id_left, min, Some(id), policy)
+            .or_else(|| self.rec_bst_violation(node.id_right, Some(id), max, policy))
+    }
+}
+
+/// Where keys equal to the key of an ancestor may be placed for a [`Tree`]
+/// to be considered a BST.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DuplicatePolicy {
+    /// Duplicates go in the left subtree. This is the rule of [`Tree::is_bst`].
+    #[default]
+    Left,
+    /// Duplicates go in the right subtree.
+    Right,
+    /// Duplicates are not allowed.
+    Strict,
+    /// Duplicates may be on either side.
+    Both,
+}
+
+impl DuplicatePolicy {
+    fn allows_left(self) -> bool {
+        matches!(self, DuplicatePolicy::Left | DuplicatePolicy::Both)
     }
 
+    fn allows_right(self) -> bool {
+        matches!(self, DuplicatePolicy::Right | DuplicatePolicy::Both)
+    }
+}
+
+/// A node breaking the BST property, as reported by [`Tree::bst_violation`].
+#[derive(Debug, PartialEq, Eq)]
+pub struct BstViolation<'a, K> {
+    /// The id of the violating node.
+    pub id: usize,
+    /// The id of the ancestor whose key bounds the violating node.
+    pub ancestor: usize,
+    /// The bound that was broken.
+    pub bound: BrokenBound<'a, K>,
+}
+
+/// The bound broken by a node of a [`Tree`] that is not a BST.
+#[derive(Debug, PartialEq, Eq)]
+pub enum BrokenBound<'a, K> {
+    /// The node is in the right subtree of an ancestor with this key, but it is smaller.
+    Lower(&'a K),
+    /// The node is in the left subtree of an ancestor with this key, but it is greater.
+    Upper(&'a K),
+}
+
+impl<K: Numeric> Tree<K> {
     /// Finds the maximum path sum in the tree.
     /// The maximum path sum is the highest sum of node values along any path
     /// from one leaf to another (may or may not pass through the root).
-    pub fn max_path_sum(&self) -> u32 {
-        let (_, sum) = self.rec_max_path_sum(Some(0));
+    /// Both child branches are always added, so with negative keys use
+    /// [`Tree::max_path`] instead.
+    pub fn max_path_sum(&self) -> K {
+        let (_, sum) = self.rec_max_path_sum(self.root);
         sum
     }
 
@@ -125,7 +586,7 @@ impl Tree {
     /// - The first value is the maximum sum that can be obtained by including
     ///   the current node and one of its subtrees.
     /// - The second value is the maximum path sum found so far in the subtree.
-    fn rec_max_path_sum(&self, node_id: Option<usize>) -> (u32, u32) {
+    fn rec_max_path_sum(&self, node_id: Option<usize>) -> (K, K) {
         if let Some(id) = node_id {
             assert!(id < self.nodes.len(), "Node id is out of range");
             let node = &self.nodes[id];
@@ -133,20 +594,192 @@ impl Tree {
             let (max_left, best_left) = self.rec_max_path_sum(node.id_left);
             let (max_right, best_right) = self.rec_max_path_sum(node.id_right);
 
-            let max_node = node.key + max_left.max(max_right);
+            let key = *node.key();
+            let max_node = key + max_left.max(max_right);
 
             // The best path sum either includes the current node or comes from a subtree
-            let current_path = node.key + max_left + max_right;
+            let current_path = key + max_left + max_right;
             let best_node = best_left.max(best_right).max(current_path);
 
             return (max_node, best_node);
         }
 
-        (0, 0)
+        (K::ZERO, K::ZERO)
+    }
+
+    /// Returns the sum of all the keys in the tree, or `None` if it overflows `K`.
+    pub fn checked_sum(&self) -> Option<K> {
+        self.rec_checked_sum(self.root)
+    }
+
+    /// Returns the sum of all the keys in the tree computed in the wider type `W`,
+    /// e.g. `tree.sum_as::<u64>()` for a tree of `u32` keys.
+    ///
+    /// # Panics
+    /// Panics if the sum overflows `W` as well.
+    pub fn sum_as<W: Numeric + From<K>>(&self) -> W {
+        self.rec_checked_sum(self.root)
+            .expect("Sum overflows the widened type")
+    }
+
+    /// A private recursive function that computes the sum of nodes in the
+    /// subtree rooted at `node_id` in the type `W`, or `None` on overflow.
+    fn rec_checked_sum<W: Numeric + From<K>>(&self, node_id: Option<usize>) -> Option<W> {
+        if let Some(id) = node_id {
+            assert!(id < self.nodes.len(), "Node id is out of range");
+            let node = &self.nodes[id];
+
+            let sum_left = self.rec_checked_sum::<W>(node.id_left)?;
+            let sum_right = self.rec_checked_sum::<W>(node.id_right)?;
+
+            return sum_left
+                .checked_add(sum_right)?
+                .checked_add(W::from(*node.key()));
+        }
+
+        Some(W::ZERO)
+    }
+
+    /// Same as [`Tree::max_path_sum`], but returns `None` if the sum overflows `K`.
+    pub fn checked_max_path_sum(&self) -> Option<K> {
+        let (_, sum) = self.rec_checked_max_path_sum(self.root)?;
+        Some(sum)
+    }
+
+    /// Same as [`Tree::max_path_sum`], but computed in the wider type `W`,
+    /// e.g. `tree.max_path_sum_as::<u64>()` for a tree of `u32` keys.
+    ///
+    /// # Panics
+    /// Panics if the sum overflows `W` as well.
+    pub fn max_path_sum_as<W: Numeric + From<K>>(&self) -> W {
+        let (_, sum) = self
+            .rec_checked_max_path_sum(self.root)
+            .expect("Path sum overflows the widened type");
+        sum
+    }
+
+    /// A private recursive function that mirrors [`Tree::rec_max_path_sum`]
+    /// in the type `W`, returning `None` as soon as an addition overflows.
+    fn rec_checked_max_path_sum<W: Numeric + From<K>>(
+        &self,
+        node_id: Option<usize>,
+    ) -> Option<(W, W)> {
+        if let Some(id) = node_id {
+            assert!(id < self.nodes.len(), "Node id is out of range");
+            let node = &self.nodes[id];
+
+            let (max_left, best_left) = self.rec_checked_max_path_sum::<W>(node.id_left)?;
+            let (max_right, best_right) = self.rec_checked_max_path_sum::<W>(node.id_right)?;
+
+            let key = W::from(*node.key());
+            let max_node = key.checked_add(max_left.max(max_right))?;
+
+            let current_path = key.checked_add(max_left)?.checked_add(max_right)?;
+            let best_node = best_left.max(best_right).max(current_path);
+
+            return Some((max_node, best_node));
+        }
+
+        Some((W::ZERO, W::ZERO))
+    }
+
+    /// Finds the path with the highest sum of node values and returns the sum
+    /// together with the ids of the nodes along the path, from one end to the other.
+    /// A path has at least one node and may start and end anywhere; branches that
+    /// would lower the sum are left out, so negative keys are handled correctly.
+    /// Returns `None` if the tree is empty.
+    pub fn max_path(&self) -> Option<(K, Vec<usize>)> {
+        let root = self.root?;
+
+        let mut next = vec![None; self.nodes.len()];
+        let mut best = None;
+        self.rec_max_path(root, &mut next, &mut best);
+
+        let best: BestPath<K> = best?;
+        let node = &self.nodes[best.top];
+
+        // Walk down the left branch and reverse it, so the path reads left to right
+        let mut path = Vec::new();
+        let mut current = best.with_left.then_some(node.id_left).flatten();
+        while let Some(id) = current {
+            path.push(id);
+            current = next[id];
+        }
+        path.reverse();
+
+        path.push(best.top);
+
+        let mut current = best.with_right.then_some(node.id_right).flatten();
+        while let Some(id) = current {
+            path.push(id);
+            current = next[id];
+        }
+
+        Some((best.sum, path))
+    }
+
+    /// A private recursive function that returns the maximum sum of a downward
+    /// path starting at `id`. The child continuing that path is stored in `next`,
+    /// and `best` is updated with the best path whose topmost node is `id`.
+    fn rec_max_path(
+        &self,
+        id: usize,
+        next: &mut [Option<usize>],
+        best: &mut Option<BestPath<K>>,
+    ) -> K {
+        assert!(id < self.nodes.len(), "Node id is out of range");
+        let node = &self.nodes[id];
+        let key = *node.key();
+
+        // A branch is only worth taking if it adds something to the sum
+        let left = node
+            .id_left
+            .map(|child| (self.rec_max_path(child, next, best), child))
+            .filter(|&(gain, _)| gain > K::ZERO);
+        let right = node
+            .id_right
+            .map(|child| (self.rec_max_path(child, next, best), child))
+            .filter(|&(gain, _)| gain > K::ZERO);
+
+        let mut current = BestPath {
+            sum: key,
+            top: id,
+            with_left: false,
+            with_right: false,
+        };
+        if let Some((gain, _)) = left {
+            current.sum = current.sum + gain;
+            current.with_left = true;
+        }
+        if let Some((gain, _)) = right {
+            current.sum = current.sum + gain;
+            current.with_right = true;
+        }
+        if best.as_ref().is_none_or(|best| current.sum > best.sum) {
+            *best = Some(current);
+        }
+
+        match left.max(right) {
+            Some((gain, child)) => {
+                next[id] = Some(child);
+                key + gain
+            }
+            None => key,
+        }
     }
 }
 
+/// The best path found so far by [`Tree::max_path`]: its sum, its topmost node
+/// and whether it continues into the left and right subtrees of that node.
+struct BestPath<K> {
+    sum: K,
+    top: usize,
+    with_left: bool,
+    with_right: bool,
+}
+
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
     use super::*;
 
@@ -268,4 +901,372 @@ mod tests {
         tree.add_node(2, 4, false);
         assert_eq!(tree.max_path_sum(), 10); // 1 + 2 + 3 + 4 = 10
     }
+
+    #[test]
+    fn test_remove_subtree_recycles_ids() {
+        let mut tree = Tree::with_root(10);
+        let left = tree.add_node(0, 5, true); // id 1
+        tree.add_node(left, 3, true); // id 2
+        tree.add_node(left, 7, false); // id 3
+        tree.add_node(0, 22, false); // id 4
+
+        assert_eq!(tree.remove_subtree(left), 3);
+        assert_eq!(tree.len(), 2);
+        assert_eq!(tree.sum(), 32);
+        assert!(!tree.contains_id(left));
+
+        // Freed slots are reused before the arena grows
+        let id = tree.add_node(0, 1, true);
+        assert!(id < 4);
+        assert_eq!(tree.nodes.len(), 5);
+        assert_eq!(tree.sum(), 33);
+    }
+
+    #[test]
+    fn test_remove_subtree_root() {
+        let mut tree = Tree::with_root(10);
+        tree.add_node(0, 5, true);
+
+        assert_eq!(tree.remove_subtree(0), 2);
+        assert!(tree.is_empty());
+        assert_eq!(tree.sum(), 0);
+
+        let root = tree.add_root(7);
+        assert_eq!(tree.root(), Some(root));
+        assert_eq!(tree.sum(), 7);
+    }
+
+    #[test]
+    fn test_stale_handle() {
+        let mut tree = Tree::with_root(10);
+        let id = tree.add_node(0, 5, true);
+        let handle = tree.handle(id).unwrap();
+        assert_eq!(tree.resolve(handle), Some(id));
+
+        tree.remove_subtree(id);
+        assert_eq!(tree.resolve(handle), None);
+        assert_eq!(tree.handle(id), None);
+
+        // The id is recycled, but the old handle keeps pointing nowhere
+        let new_id = tree.add_node(0, 6, true);
+        assert_eq!(new_id, id);
+        assert_eq!(tree.resolve(handle), None);
+        assert_eq!(tree.resolve(tree.handle(new_id).unwrap()), Some(new_id));
+    }
+
+    #[test]
+    fn test_remove_node_single_child() {
+        let mut tree = Tree::with_root(10);
+        let left = tree.add_node(0, 5, true); // id 1
+        tree.add_node(left, 3, true); // id 2
+
+        assert_eq!(tree.remove_node(left, ReattachPolicy::PromoteLeft), 5);
+        assert_eq!(tree.nodes[0].id_left, Some(2));
+        assert_eq!(tree.sum(), 13);
+    }
+
+    #[test]
+    fn test_remove_node_promote_left() {
+        let mut tree = Tree::with_root(10);
+        tree.add_node(0, 5, true); // id 1
+        tree.add_node(0, 15, false); // id 2
+        tree.add_node(1, 3, true); // id 3
+        tree.add_node(1, 7, false); // id 4
+
+        assert_eq!(tree.remove_node(0, ReattachPolicy::PromoteLeft), 10);
+        assert_eq!(tree.root(), Some(1));
+        assert_eq!(tree.nodes[4].id_right, Some(2));
+        assert!(tree.is_bst());
+        assert_eq!(tree.sum(), 30);
+    }
+
+    #[test]
+    fn test_remove_node_promote_right() {
+        let mut tree = Tree::with_root(10);
+        tree.add_node(0, 5, true); // id 1
+        tree.add_node(0, 15, false); // id 2
+        tree.add_node(2, 12, true); // id 3
+        tree.add_node(2, 20, false); // id 4
+
+        assert_eq!(tree.remove_node(0, ReattachPolicy::PromoteRight), 10);
+        assert_eq!(tree.root(), Some(2));
+        assert_eq!(tree.nodes[3].id_left, Some(1));
+        assert!(tree.is_bst());
+    }
+
+    #[test]
+    #[should_panic(expected = "Parent node id does not exist")]
+    fn test_add_node_to_removed_parent() {
+        let mut tree = Tree::with_root(10);
+        let id = tree.add_node(0, 5, true);
+        tree.remove_node(id, ReattachPolicy::PromoteLeft);
+        tree.add_node(id, 1, true);
+    }
+
+    #[test]
+    fn test_signed_keys() {
+        let mut tree: Tree<i64> = Tree::with_root(-10);
+        tree.add_node(0, 4, true);
+        tree.add_node(0, -3, false);
+
+        assert_eq!(tree.sum(), -9);
+        assert!(!tree.is_bst());
+    }
+
+    #[test]
+    fn test_sum_u32() {
+        let mut tree: Tree<u32> = Tree::with_root(10);
+        tree.add_node(0, 5, true);
+        tree.add_node(0, 22, false);
+        tree.add_node(1, 7, false);
+        tree.add_node(2, 20, true);
+        assert_eq!(tree.sum(), 64);
+    }
+
+    #[test]
+    fn test_is_bst_u32() {
+        let mut tree: Tree<u32> = Tree::with_root(10);
+        tree.add_node(0, 5, true);
+        tree.add_node(1, 10, false); // Duplicate on the left is valid
+        tree.add_node(0, 15, false);
+        assert!(tree.is_bst());
+
+        tree.add_node(3, 10, true); // Invalid: duplicate on the right
+        assert!(!tree.is_bst());
+    }
+
+    #[test]
+    fn test_max_path_sum_u32() {
+        let mut tree: Tree<u32> = Tree::with_root(10);
+        tree.add_node(0, 5, true);
+        tree.add_node(0, 15, false);
+        tree.add_node(1, 3, true);
+        tree.add_node(1, 7, false);
+        tree.add_node(2, 12, true);
+        tree.add_node(2, 20, false);
+        assert_eq!(tree.max_path_sum(), 57); // Path: 7 -> 5 -> 10 -> 15 -> 20
+    }
+
+    #[test]
+    fn test_string_keys_is_bst() {
+        let mut tree = Tree::with_root(String::from("m"));
+        let left = tree.add_node(0, String::from("c"), true);
+        tree.add_node(left, String::from("f"), false);
+        tree.add_node(0, String::from("x"), false);
+        assert!(tree.is_bst());
+
+        tree.add_node(left, String::from("z"), true);
+        assert!(!tree.is_bst());
+    }
+
+    #[test]
+    fn test_is_bst_min_key() {
+        let mut tree: Tree<u32> = Tree::with_root(5);
+        tree.add_node(0, 0, true);
+        assert!(tree.is_bst());
+    }
+
+    #[test]
+    fn test_max_path_sum_wide_keys() {
+        let mut tree: Tree<u64> = Tree::with_root(u32::MAX as u64);
+        tree.add_node(0, u32::MAX as u64, true);
+        assert_eq!(tree.max_path_sum(), 2 * u32::MAX as u64);
+    }
+
+    #[test]
+    fn test_max_path_single_node() {
+        let tree: Tree<i32> = Tree::with_root(-7);
+        assert_eq!(tree.max_path(), Some((-7, vec![0])));
+    }
+
+    #[test]
+    fn test_max_path_empty() {
+        let tree: Tree<i32> = Tree::new();
+        assert_eq!(tree.max_path(), None);
+    }
+
+    #[test]
+    fn test_max_path_skips_negative_branches() {
+        let mut tree: Tree<i32> = Tree::with_root(-10);
+        tree.add_node(0, 9, true); // id 1
+        tree.add_node(0, 20, false); // id 2
+        tree.add_node(2, 15, true); // id 3
+        tree.add_node(2, 7, false); // id 4
+        tree.add_node(3, -4, true); // id 5
+
+        assert_eq!(tree.max_path(), Some((42, vec![3, 2, 4])));
+    }
+
+    #[test]
+    fn test_max_path_through_root() {
+        let mut tree: Tree<i32> = Tree::with_root(1);
+        tree.add_node(0, 2, true); // id 1
+        tree.add_node(0, 3, false); // id 2
+        tree.add_node(1, -1, true); // id 3
+        tree.add_node(1, 4, false); // id 4
+
+        assert_eq!(tree.max_path(), Some((10, vec![4, 1, 0, 2])));
+    }
+
+    #[test]
+    fn test_max_path_all_negative() {
+        let mut tree: Tree<i32> = Tree::with_root(-3);
+        tree.add_node(0, -1, true);
+        tree.add_node(0, -2, false);
+
+        assert_eq!(tree.max_path(), Some((-1, vec![1])));
+    }
+
+    #[test]
+    fn test_max_path_matches_max_path_sum() {
+        let mut tree = Tree::with_root(10);
+        tree.add_node(0, 5, true);
+        tree.add_node(0, 15, false);
+        tree.add_node(1, 3, true);
+        tree.add_node(1, 7, false);
+        tree.add_node(2, 12, true);
+        tree.add_node(2, 20, false);
+
+        assert_eq!(tree.max_path(), Some((57, vec![4, 1, 0, 2, 6])));
+    }
+
+    #[test]
+    fn test_checked_sum() {
+        let mut tree: Tree<u32> = Tree::with_root(10);
+        tree.add_node(0, 5, true);
+        assert_eq!(tree.checked_sum(), Some(15));
+
+        tree.add_node(0, u32::MAX, false);
+        assert_eq!(tree.checked_sum(), None);
+        assert_eq!(tree.sum_as::<u64>(), u32::MAX as u64 + 15);
+        assert_eq!(tree.sum_as::<u128>(), u32::MAX as u128 + 15);
+    }
+
+    #[test]
+    fn test_checked_max_path_sum() {
+        let mut tree: Tree<u32> = Tree::with_root(u32::MAX / 2);
+        tree.add_node(0, u32::MAX / 2, true);
+        assert_eq!(tree.checked_max_path_sum(), Some(u32::MAX - 1));
+
+        tree.add_node(0, 2, false);
+        assert_eq!(tree.checked_max_path_sum(), None);
+        assert_eq!(tree.max_path_sum_as::<u64>(), u32::MAX as u64 + 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Sum overflows the widened type")]
+    fn test_sum_as_overflow() {
+        let mut tree: Tree<u8> = Tree::with_root(200);
+        tree.add_node(0, 200, true);
+        tree.sum_as::<u8>();
+    }
+
+    #[test]
+    fn test_try_add_node_errors() {
+        let mut tree = Tree::with_root(10);
+        let left = tree.try_add_node(0, 5, true).unwrap();
+
+        assert_eq!(
+            tree.try_add_node(7, 1, true),
+            Err(TreeError::UnknownParent(7))
+        );
+        assert_eq!(
+            tree.try_add_node(0, 1, true),
+            Err(TreeError::LeftOccupied(0))
+        );
+        assert_eq!(tree.try_add_node(left, 1, false), Ok(2));
+        assert_eq!(
+            tree.try_add_node(left, 1, false),
+            Err(TreeError::RightOccupied(left))
+        );
+        assert_eq!(tree.len(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "Parent node has the right child already set")]
+    fn test_add_node_occupied() {
+        let mut tree = Tree::with_root(10);
+        tree.add_node(0, 5, false);
+        tree.add_node(0, 6, false);
+    }
+
+    #[test]
+    fn test_replace_child() {
+        let mut tree = Tree::with_root(10);
+        let left = tree.add_node(0, 5, true); // id 1
+        tree.add_node(left, 3, true); // id 2
+
+        let new_left = tree.replace_child(0, 4, true).unwrap();
+        assert_eq!(tree.nodes[0].id_left, Some(new_left));
+        assert_eq!(tree.len(), 2);
+        assert_eq!(tree.sum(), 14);
+
+        let right = tree.replace_child(0, 20, false).unwrap();
+        assert_eq!(tree.nodes[0].id_right, Some(right));
+        assert_eq!(tree.sum(), 34);
+
+        assert_eq!(
+            tree.replace_child(9, 1, true),
+            Err(TreeError::UnknownParent(9))
+        );
+    }
+
+    #[test]
+    fn test_is_bst_duplicate_policies() {
+        let mut left = Tree::with_root(10);
+        left.add_node(0, 10, true);
+
+        let mut right = Tree::with_root(10);
+        right.add_node(0, 10, false);
+
+        assert!(left.is_bst_with(DuplicatePolicy::Left));
+        assert!(!left.is_bst_with(DuplicatePolicy::Right));
+        assert!(!left.is_bst_with(DuplicatePolicy::Strict));
+        assert!(left.is_bst_with(DuplicatePolicy::Both));
+
+        assert!(!right.is_bst_with(DuplicatePolicy::Left));
+        assert!(right.is_bst_with(DuplicatePolicy::Right));
+        assert!(!right.is_bst_with(DuplicatePolicy::Strict));
+        assert!(right.is_bst_with(DuplicatePolicy::Both));
+    }
+
+    #[test]
+    fn test_is_bst_full_key_range() {
+        let mut tree: Tree<u32> = Tree::with_root(u32::MAX);
+        tree.add_node(0, 0, true);
+        tree.add_node(1, 0, true);
+        assert!(tree.is_bst());
+        assert!(!tree.is_bst_with(DuplicatePolicy::Strict));
+    }
+
+    #[test]
+    fn test_bst_violation() {
+        let mut tree = Tree::with_root(10);
+        tree.add_node(0, 5, true); // id 1
+        tree.add_node(1, 3, true); // id 2
+        tree.add_node(2, 4, false); // id 3
+        tree.add_node(3, 1, true); // id 4
+        tree.add_node(3, 15, false); // id 5
+
+        assert_eq!(
+            tree.bst_violation(DuplicatePolicy::Left),
+            Some(BstViolation {
+                id: 4,
+                ancestor: 2,
+                bound: BrokenBound::Lower(&3),
+            })
+        );
+
+        let mut tree = Tree::with_root(10);
+        tree.add_node(0, 5, true); // id 1
+        tree.add_node(1, 12, false); // id 2
+        assert_eq!(
+            tree.bst_violation(DuplicatePolicy::Left),
+            Some(BstViolation {
+                id: 2,
+                ancestor: 0,
+                bound: BrokenBound::Upper(&10),
+            })
+        );
+    }
 }
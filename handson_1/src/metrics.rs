@@ -0,0 +1,183 @@
+use crate::Tree;
+
+/// Structural metrics of a [`Tree`], computed by [`Tree::metrics`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TreeMetrics {
+    /// The number of nodes.
+    pub size: usize,
+    /// The number of nodes on the longest path from the root to a leaf.
+    pub height: usize,
+    /// The number of nodes without children.
+    pub leaves: usize,
+    /// The number of edges on the longest path between two nodes.
+    pub diameter: usize,
+    /// The number of nodes on each level, starting from the root.
+    pub widths: Vec<usize>,
+    /// Whether the heights of the two subtrees of every node differ by at most one.
+    pub is_balanced: bool,
+    /// Whether every level is full, except possibly the last one, which is filled from the left.
+    pub is_complete: bool,
+    /// Whether every node has either zero or two children.
+    pub is_full: bool,
+    /// Whether every level is full.
+    pub is_perfect: bool,
+}
+
+impl<K> Tree<K> {
+    /// Computes the structural metrics of the tree in a single traversal.
+    /// The traversal uses an explicit stack, so it works on trees of any depth.
+    pub fn metrics(&self) -> TreeMetrics {
+        let mut metrics = TreeMetrics {
+            is_balanced: true,
+            is_full: true,
+            ..Default::default()
+        };
+
+        // The height of the subtree rooted at each id, filled in post-order
+        let mut heights: Vec<usize> = vec![0; self.nodes.len()];
+        // The largest position in level order, numbering the root 1 and the
+        // children of node i as 2i and 2i + 1; `None` once it overflows
+        let mut max_position = Some(0);
+
+        // Each node is pushed twice: first to visit it top-down, then to combine its children
+        let mut stack: Vec<(usize, usize, Option<usize>, bool)> = self
+            .root
+            .map(|root| (root, 0, Some(1), false))
+            .into_iter()
+            .collect();
+
+        while let Some((id, depth, position, combine)) = stack.pop() {
+            let node = &self.nodes[id];
+
+            if !combine {
+                metrics.size += 1;
+                if metrics.widths.len() == depth {
+                    metrics.widths.push(0);
+                }
+                metrics.widths[depth] += 1;
+                max_position = max_position.zip(position).map(|(max, pos)| max.max(pos));
+
+                if node.id_left.is_some() != node.id_right.is_some() {
+                    metrics.is_full = false;
+                }
+
+                let left_position = position.and_then(|pos| pos.checked_mul(2));
+                let right_position = left_position.and_then(|pos| pos.checked_add(1));
+                stack.push((id, depth, position, true));
+                if let Some(right) = node.id_right {
+                    stack.push((right, depth + 1, right_position, false));
+                }
+                if let Some(left) = node.id_left {
+                    stack.push((left, depth + 1, left_position, false));
+                }
+                continue;
+            }
+
+            let height_left = node.id_left.map_or(0, |left| heights[left]);
+            let height_right = node.id_right.map_or(0, |right| heights[right]);
+            heights[id] = 1 + height_left.max(height_right);
+
+            if node.id_left.is_none() && node.id_right.is_none() {
+                metrics.leaves += 1;
+            }
+            if height_left.abs_diff(height_right) > 1 {
+                metrics.is_balanced = false;
+            }
+
+            // The longest path through this node goes down both subtrees
+            metrics.diameter = metrics.diameter.max(height_left + height_right);
+        }
+
+        metrics.height = metrics.widths.len();
+
+        // Without gaps in level order, the last position is the number of nodes
+        metrics.is_complete = max_position == Some(metrics.size);
+        metrics.is_perfect = metrics.is_complete
+            && metrics
+                .widths
+                .iter()
+                .enumerate()
+                .all(|(level, &width)| level < usize::BITS as usize && width == 1 << level);
+
+        metrics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metrics() {
+        let mut tree = Tree::with_root(10);
+        tree.add_node(0, 5, true); // id 1
+        tree.add_node(0, 22, false); // id 2
+        tree.add_node(1, 7, false); // id 3
+        tree.add_node(3, 6, true); // id 4
+        tree.add_node(2, 20, true); // id 5
+
+        assert_eq!(
+            tree.metrics(),
+            TreeMetrics {
+                size: 6,
+                height: 4,
+                leaves: 2,
+                diameter: 5,
+                widths: vec![1, 2, 2, 1],
+                is_balanced: false,
+                is_complete: false,
+                is_full: false,
+                is_perfect: false,
+            }
+        );
+    }
+
+    #[test]
+    fn test_metrics_empty() {
+        let metrics = Tree::<u32>::new().metrics();
+        assert_eq!(metrics.size, 0);
+        assert_eq!(metrics.height, 0);
+        assert_eq!(metrics.diameter, 0);
+        assert!(metrics.is_complete && metrics.is_perfect);
+    }
+
+    #[test]
+    fn test_metrics_shapes() {
+        let perfect: Tree<u32> = "[1, 2, 3, 4, 5, 6, 7]".parse().unwrap();
+        let metrics = perfect.metrics();
+        assert!(metrics.is_balanced && metrics.is_complete && metrics.is_full);
+        assert!(metrics.is_perfect);
+        assert_eq!(metrics.leaves, 4);
+        assert_eq!(metrics.diameter, 4);
+
+        let complete: Tree<u32> = "[1, 2, 3, 4, 5, 6]".parse().unwrap();
+        let metrics = complete.metrics();
+        assert!(metrics.is_balanced && metrics.is_complete);
+        assert!(!metrics.is_full && !metrics.is_perfect);
+
+        let full: Tree<u32> = "[1, 2, 3, null, null, 6, 7]".parse().unwrap();
+        let metrics = full.metrics();
+        assert!(metrics.is_balanced && metrics.is_full);
+        assert!(!metrics.is_complete && !metrics.is_perfect);
+
+        let gap: Tree<u32> = "[1, 2, 3, 4, null, 6]".parse().unwrap();
+        assert!(!gap.metrics().is_complete);
+    }
+
+    #[test]
+    fn test_metrics_deep_chain() {
+        let mut tree = Tree::with_root(0);
+        let mut last = 0;
+        for key in 1..1_000_000 {
+            last = tree.add_node(last, key, false);
+        }
+
+        let metrics = tree.metrics();
+        assert_eq!(metrics.size, 1_000_000);
+        assert_eq!(metrics.height, 1_000_000);
+        assert_eq!(metrics.leaves, 1);
+        assert_eq!(metrics.diameter, 999_999);
+        assert!(metrics.widths.iter().all(|&width| width == 1));
+        assert!(!metrics.is_balanced && !metrics.is_complete && !metrics.is_perfect);
+    }
+}
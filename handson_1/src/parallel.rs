@@ -0,0 +1,211 @@
+use std::collections::{HashMap, VecDeque};
+use std::num::NonZeroUsize;
+use std::ops::Add;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use crate::{Numeric, Tree};
+
+impl<K: Sync> Tree<K> {
+    /// A private function that folds the tree bottom-up with `combine`, which gets
+    /// the key of a node and the values of its children, on all available cores.
+    ///
+    /// The top of the tree is expanded breadth-first until there are a few
+    /// subtrees per thread, never expanding leaves and at most a few nodes per
+    /// thread, so that trees which do not branch much stay cheap to split.
+    /// The threads then take the subtrees one at a time, and the values of the
+    /// expanded nodes are combined at the end. With a single core, the whole
+    /// tree is folded on the calling thread.
+    fn par_fold<T, F>(&self, combine: F) -> Option<T>
+    where
+        T: Send,
+        F: Fn(&K, Option<T>, Option<T>) -> T + Sync,
+    {
+        let root = self.root?;
+        let threads = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+        if threads == 1 {
+            return Some(self.fold_subtree(root, &combine));
+        }
+
+        let limit = 4 * threads;
+        let mut top = Vec::new();
+        let mut queue = VecDeque::from([root]);
+        let mut subtrees = Vec::new();
+        while queue.len() + subtrees.len() < limit && top.len() < limit {
+            let Some(id) = queue.pop_front() else {
+                break;
+            };
+            let node = &self.nodes[id];
+            if node.id_left.is_none() && node.id_right.is_none() {
+                subtrees.push(id);
+                continue;
+            }
+            top.push(id);
+            queue.extend(node.id_left.into_iter().chain(node.id_right));
+        }
+        subtrees.extend(queue);
+
+        let next = AtomicUsize::new(0);
+        let mut values: HashMap<usize, T> = thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads.min(subtrees.len()))
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut values = Vec::new();
+                        loop {
+                            let i = next.fetch_add(1, Ordering::Relaxed);
+                            let Some(&id) = subtrees.get(i) else {
+                                break values;
+                            };
+                            values.push((id, self.fold_subtree(id, &combine)));
+                        }
+                    })
+                })
+                .collect();
+
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().expect("Worker thread panicked"))
+                .collect()
+        });
+
+        // Children come after their parents in breadth-first order
+        for &id in top.iter().rev() {
+            let node = &self.nodes[id];
+            let left = node.id_left.map(|left| values.remove(&left).unwrap());
+            let right = node.id_right.map(|right| values.remove(&right).unwrap());
+            values.insert(id, combine(node.key(), left, right));
+        }
+
+        values.remove(&root)
+    }
+
+    /// A private function that folds the subtree rooted at `root` bottom-up with
+    /// `combine`, using explicit stacks so that deep subtrees fit the thread stack.
+    fn fold_subtree<T, F>(&self, root: usize, combine: &F) -> T
+    where
+        F: Fn(&K, Option<T>, Option<T>) -> T,
+    {
+        // Each node is pushed twice: first to visit its children, then to combine them
+        let mut stack = vec![(root, false)];
+        let mut values = Vec::new();
+
+        while let Some((id, combine_children)) = stack.pop() {
+            let node = &self.nodes[id];
+
+            if !combine_children {
+                stack.push((id, true));
+                stack.extend(node.id_right.map(|right| (right, false)));
+                stack.extend(node.id_left.map(|left| (left, false)));
+                continue;
+            }
+
+            // The left subtree is done first, so its value lies below the right one
+            let right = node.id_right.map(|_| values.pop().unwrap());
+            let left = node.id_left.map(|_| values.pop().unwrap());
+            values.push(combine(node.key(), left, right));
+        }
+
+        values.pop().unwrap()
+    }
+}
+
+impl<K: Copy + Default + Add<Output = K> + Send + Sync> Tree<K> {
+    /// Same as [`Tree::sum`], but splits the work by subtree across all available cores.
+    pub fn par_sum(&self) -> K {
+        self.par_fold(|&key, sum_left: Option<K>, sum_right: Option<K>| {
+            sum_left.unwrap_or_default() + sum_right.unwrap_or_default() + key
+        })
+        .unwrap_or_default()
+    }
+}
+
+impl<K: Numeric + Send + Sync> Tree<K> {
+    /// Same as [`Tree::max_path_sum`], but splits the work by subtree across all available cores.
+    pub fn par_max_path_sum(&self) -> K {
+        let (_, sum) = self
+            .par_fold(|&key, left: Option<(K, K)>, right: Option<(K, K)>| {
+                let (max_left, best_left) = left.unwrap_or((K::ZERO, K::ZERO));
+                let (max_right, best_right) = right.unwrap_or((K::ZERO, K::ZERO));
+
+                let max_node = key + max_left.max(max_right);
+                let current_path = key + max_left + max_right;
+                (max_node, best_left.max(best_right).max(current_path))
+            })
+            .unwrap_or((K::ZERO, K::ZERO));
+        sum
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a tree of `n` nodes hanging each node from a pseudo-random earlier one.
+    fn random_tree(n: usize) -> Tree<u64> {
+        let mut tree = Tree::with_root(0);
+        let mut state: u64 = 7;
+        while tree.len() < n {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            let parent = (state >> 33) as usize % tree.len();
+            let key = (state >> 40) % 1000;
+            let _ = tree.try_add_node(parent, key, state & 1 == 0);
+        }
+        tree
+    }
+
+    #[test]
+    fn test_par_sum() {
+        let tree = random_tree(200_000);
+        assert_eq!(tree.par_sum(), tree.sum());
+    }
+
+    #[test]
+    fn test_par_max_path_sum() {
+        let tree = random_tree(200_000);
+        assert_eq!(tree.par_max_path_sum(), tree.max_path_sum());
+    }
+
+    #[test]
+    fn test_par_small_trees() {
+        assert_eq!(Tree::<u32>::new().par_sum(), 0);
+        assert_eq!(Tree::<u32>::new().par_max_path_sum(), 0);
+
+        let mut tree = Tree::with_root(10);
+        assert_eq!(tree.par_sum(), 10);
+        tree.add_node(0, 5, true);
+        tree.add_node(0, 15, false);
+        tree.add_node(1, 3, true);
+        tree.add_node(1, 7, false);
+        tree.add_node(2, 12, true);
+        tree.add_node(2, 20, false);
+        assert_eq!(tree.par_sum(), 72);
+        assert_eq!(tree.par_max_path_sum(), 57);
+    }
+
+    #[test]
+    fn test_par_caterpillar() {
+        // A long spine with a leaf hanging from every node
+        let mut tree: Tree<u64> = Tree::with_root(1);
+        let mut last = 0;
+        for key in 2..=2_000 {
+            tree.add_node(last, key * 2, key % 2 == 0);
+            last = tree.add_node(last, key, key % 2 == 1);
+        }
+        assert_eq!(tree.len(), 3_999);
+        assert_eq!(tree.par_sum(), tree.sum());
+        assert_eq!(tree.par_max_path_sum(), tree.max_path_sum());
+    }
+
+    #[test]
+    fn test_par_deep_chain() {
+        let mut tree: Tree<u64> = Tree::with_root(1);
+        let mut last = 0;
+        for key in 2..=1_000_000 {
+            last = tree.add_node(last, key, key % 2 == 0);
+        }
+        assert_eq!(tree.par_sum(), 500_000_500_000);
+        assert_eq!(tree.par_max_path_sum(), 500_000_500_000);
+    }
+}
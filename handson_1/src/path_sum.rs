@@ -0,0 +1,228 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::{Numeric, Tree};
+
+/// An exact sum of keys, which may not fit in `K`: `low` plus `wraps` times
+/// the number of values of `K`, with `low` wrapped into the range of `K`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct WideSum<K> {
+    wraps: i64,
+    low: K,
+}
+
+impl<K: Numeric> WideSum<K> {
+    fn add(self, rhs: K) -> Self {
+        match self.low.checked_add(rhs) {
+            Some(low) => Self { low, ..self },
+            None => Self {
+                wraps: self.wraps + if rhs > K::ZERO { 1 } else { -1 },
+                low: self.low.wrapping_add(rhs),
+            },
+        }
+    }
+
+    fn sub(self, rhs: K) -> Self {
+        match self.low.checked_sub(rhs) {
+            Some(low) => Self { low, ..self },
+            None => Self {
+                wraps: self.wraps + if rhs > K::ZERO { -1 } else { 1 },
+                low: self.low.wrapping_sub(rhs),
+            },
+        }
+    }
+}
+
+impl<K: Numeric + Hash> Tree<K> {
+    /// Returns the number of downward paths whose keys sum to `target`.
+    /// A downward path starts at any node and ends at the same node or at one
+    /// of its descendants. It runs in linear time.
+    pub fn count_paths_with_sum(&self, target: K) -> usize {
+        let mut count = 0;
+        self.for_each_path_with_sum(target, |_, starts| count += starts.len());
+        count
+    }
+
+    /// Returns the downward paths whose keys sum to `target`, as in
+    /// [`Tree::count_paths_with_sum`], each as the ids of its nodes from the top.
+    /// Paths are sorted by their last node in pre-order, then from the longest.
+    pub fn paths_with_sum(&self, target: K) -> Vec<Vec<usize>> {
+        let mut paths = Vec::new();
+        self.for_each_path_with_sum(target, |path, starts| {
+            paths.extend(starts.iter().map(|&start| path[start..].to_vec()));
+        });
+        paths
+    }
+
+    /// Returns the paths from the root to a leaf whose keys sum to `target`,
+    /// each as the ids of its nodes from the root, sorted by leaf in pre-order.
+    pub fn root_to_leaf_paths_with_sum(&self, target: K) -> Vec<Vec<usize>> {
+        let mut paths = Vec::new();
+        self.for_each_path_with_sum(target, |path, starts| {
+            let node = &self.nodes[path[path.len() - 1]];
+            let is_leaf = node.id_left.is_none() && node.id_right.is_none();
+            // Starts are increasing, so a path from the root comes first
+            if is_leaf && starts.first() == Some(&0) {
+                paths.push(path.to_vec());
+            }
+        });
+        paths
+    }
+
+    /// A private function that visits the nodes in pre-order and calls `visit`
+    /// with the path from the root to each node and the positions on it where
+    /// the downward paths ending at the node with sum `target` start.
+    ///
+    /// A path from position `i` sums to `target` iff the sum of the keys before
+    /// `i` equals the sum of the whole path minus `target`, so the positions of
+    /// the sums of the prefixes are kept in a hash map. The sums are kept exact
+    /// even where they overflow `K`, since a matching path may start below them.
+    fn for_each_path_with_sum<F>(&self, target: K, mut visit: F)
+    where
+        F: FnMut(&[usize], &[usize]),
+    {
+        let mut path = Vec::new();
+        // The sums of the prefixes of `path`, from the empty one
+        let zero = WideSum {
+            wraps: 0,
+            low: K::ZERO,
+        };
+        let mut sums = vec![zero];
+        let mut positions: HashMap<WideSum<K>, Vec<usize>> = HashMap::from([(zero, vec![0])]);
+
+        // Each node is pushed twice: first to enter it, then to leave it
+        let mut stack: Vec<(usize, bool)> =
+            self.root.map(|root| (root, false)).into_iter().collect();
+        while let Some((id, leave)) = stack.pop() {
+            if leave {
+                let sum = sums.pop().unwrap();
+                positions.get_mut(&sum).unwrap().pop();
+                path.pop();
+                continue;
+            }
+
+            let node = &self.nodes[id];
+            let sum = sums[sums.len() - 1].add(*node.key());
+            path.push(id);
+
+            let starts = positions
+                .get(&sum.sub(target))
+                .map_or(&[][..], Vec::as_slice);
+            visit(&path, starts);
+
+            positions.entry(sum).or_default().push(path.len());
+            sums.push(sum);
+
+            stack.push((id, true));
+            stack.extend(node.id_right.map(|right| (right, false)));
+            stack.extend(node.id_left.map(|left| (left, false)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Tree<i32> {
+        "[10, 5, -3, 3, 2, null, 11, 3, -2, null, 1]"
+            .parse()
+            .unwrap()
+    }
+
+    /// Counts the downward paths with sum `target` by trying every start.
+    fn brute_force_count(tree: &Tree<i32>, target: i32) -> usize {
+        let mut count = 0;
+        for start in (0..tree.nodes.len()).filter(|&id| tree.contains_id(id)) {
+            let mut stack = vec![(start, 0)];
+            while let Some((id, sum)) = stack.pop() {
+                let node = &tree.nodes[id];
+                let sum = sum + node.key();
+                if sum == target {
+                    count += 1;
+                }
+                stack.extend(
+                    node.id_left
+                        .into_iter()
+                        .chain(node.id_right)
+                        .map(|c| (c, sum)),
+                );
+            }
+        }
+        count
+    }
+
+    #[test]
+    fn test_count_paths_with_sum() {
+        let tree = sample();
+        assert_eq!(tree.count_paths_with_sum(8), 3);
+        for target in -5..30 {
+            assert_eq!(
+                tree.count_paths_with_sum(target),
+                brute_force_count(&tree, target)
+            );
+        }
+        assert_eq!(Tree::<i32>::new().count_paths_with_sum(0), 0);
+    }
+
+    #[test]
+    fn test_paths_with_sum() {
+        let tree = sample();
+        // Ids: 10 #0, 5 #1, -3 #2, 3 #3, 2 #4, 11 #5, 3 #6, -2 #7, 1 #8
+        assert_eq!(
+            tree.paths_with_sum(8),
+            vec![vec![1, 3], vec![1, 4, 8], vec![2, 5]]
+        );
+
+        // Zero-sum paths may overlap and nest
+        let tree: Tree<i32> = "[1, -1, null, 1, null, -1]".parse().unwrap();
+        assert_eq!(
+            tree.paths_with_sum(0),
+            vec![vec![0, 1], vec![1, 2], vec![0, 1, 2, 3], vec![2, 3]]
+        );
+    }
+
+    #[test]
+    fn test_paths_with_sum_unsigned() {
+        let tree: Tree<u32> = "[1, 2, 3, 4]".parse().unwrap();
+        assert_eq!(tree.paths_with_sum(6), vec![vec![1, 3]]);
+        assert_eq!(tree.count_paths_with_sum(3), 2);
+        assert_eq!(tree.count_paths_with_sum(100), 0);
+    }
+
+    #[test]
+    fn test_root_to_leaf_paths_with_sum() {
+        let tree: Tree<i32> = "[5, 4, 8, 11, null, 13, 4, 7, 2, null, null, 5, 1]"
+            .parse()
+            .unwrap();
+        // Ids: 5 #0, 4 #1, 8 #2, 11 #3, 13 #4, 4 #5, 7 #6, 2 #7, 5 #8, 1 #9
+        assert_eq!(
+            tree.root_to_leaf_paths_with_sum(22),
+            vec![vec![0, 1, 3, 7], vec![0, 2, 5, 8]]
+        );
+        // Paths must end at a leaf
+        assert!(tree.root_to_leaf_paths_with_sum(9).is_empty());
+        assert_eq!(tree.root_to_leaf_paths_with_sum(27).len(), 1);
+    }
+
+    #[test]
+    fn test_paths_with_sum_overflow() {
+        // The sums from the root overflow the key type, the matching paths do not
+        let tree: Tree<i32> = "[1000000000, 1000000000, null, 1000000000]"
+            .parse()
+            .unwrap();
+        assert_eq!(tree.count_paths_with_sum(1_000_000_000), 3);
+        assert_eq!(tree.count_paths_with_sum(2_000_000_000), 2);
+
+        let tree: Tree<u32> = "[3000000000, 3000000000]".parse().unwrap();
+        assert_eq!(tree.paths_with_sum(3_000_000_000), vec![vec![0], vec![1]]);
+
+        let tree: Tree<u8> = "[200, 100]".parse().unwrap();
+        assert_eq!(tree.count_paths_with_sum(1), 0);
+        assert_eq!(tree.count_paths_with_sum(44), 0);
+
+        let tree: Tree<i8> = "[-100, -100, 100, 50]".parse().unwrap();
+        assert_eq!(tree.count_paths_with_sum(-100), 2);
+        assert_eq!(tree.count_paths_with_sum(-50), 1);
+    }
+}
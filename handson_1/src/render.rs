@@ -0,0 +1,161 @@
+use std::fmt::{Display, Write};
+
+use crate::Tree;
+
+impl<K: Display> Tree<K> {
+    /// Renders the tree in the Graphviz DOT language. Every node is labelled
+    /// with its id and key, and the nodes in `highlight` are filled, e.g. the
+    /// ids returned by [`Tree::max_path`] or the node of a [`Tree::bst_violation`].
+    pub fn to_dot(&self, highlight: &[usize]) -> String {
+        let mut dot = String::from("digraph Tree {\n    node [shape=circle];\n");
+
+        let mut stack = Vec::from_iter(self.root);
+        while let Some(id) = stack.pop() {
+            let node = &self.nodes[id];
+
+            let label = escape(&format!("#{id}: {}", node.key()));
+            let style = if highlight.contains(&id) {
+                ", style=filled, fillcolor=gold"
+            } else {
+                ""
+            };
+            writeln!(dot, "    {id} [label=\"{label}\"{style}];").unwrap();
+
+            // The ports keep left children on the left even when the right one is missing
+            if let Some(left) = node.id_left {
+                writeln!(dot, "    {id} -> {left} [tailport=sw];").unwrap();
+            }
+            if let Some(right) = node.id_right {
+                writeln!(dot, "    {id} -> {right} [tailport=se];").unwrap();
+            }
+
+            stack.extend(node.id_right.into_iter().chain(node.id_left));
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Renders the tree as indented text for the terminal, one node per line
+    /// with its side, key and id, e.g.
+    ///
+    /// ```text
+    /// 10 #0
+    /// |-- L: 5 #1
+    /// |   `-- R: 7 #3
+    /// `-- R: 22 #2
+    /// ```
+    pub fn to_ascii(&self) -> String {
+        let mut ascii = String::new();
+        let Some(root) = self.root else {
+            return ascii;
+        };
+
+        let root_node = &self.nodes[root];
+        writeln!(ascii, "{} #{root}", root_node.key()).unwrap();
+
+        // Each entry holds the prefix of its line, the node and the side it hangs on
+        let mut stack = Vec::new();
+        let push_children = |stack: &mut Vec<_>, prefix: &str, id: usize| {
+            let node = &self.nodes[id];
+            let children = [(node.id_left, "L"), (node.id_right, "R")];
+            let children: Vec<_> = children
+                .into_iter()
+                .filter_map(|(child, side)| child.map(|child| (child, side)))
+                .collect();
+
+            for (i, &(child, side)) in children.iter().enumerate().rev() {
+                let is_last = i == children.len() - 1;
+                stack.push((prefix.to_string(), child, side, is_last));
+            }
+        };
+        push_children(&mut stack, "", root);
+
+        while let Some((prefix, id, side, is_last)) = stack.pop() {
+            let (branch, indent) = if is_last {
+                ("`-- ", "    ")
+            } else {
+                ("|-- ", "|   ")
+            };
+            writeln!(
+                ascii,
+                "{prefix}{branch}{side}: {} #{id}",
+                self.nodes[id].key()
+            )
+            .unwrap();
+            push_children(&mut stack, &format!("{prefix}{indent}"), id);
+        }
+
+        ascii
+    }
+}
+
+/// Escapes the characters with a special meaning in a quoted DOT string.
+fn escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Tree<u32> {
+        "[10, 5, 22, null, 7, 20]".parse().unwrap()
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let tree = sample();
+        let (_, path) = tree.max_path().unwrap();
+
+        assert_eq!(
+            tree.to_dot(&path),
+            "digraph Tree {
+    node [shape=circle];
+    0 [label=\"#0: 10\", style=filled, fillcolor=gold];
+    0 -> 1 [tailport=sw];
+    0 -> 2 [tailport=se];
+    1 [label=\"#1: 5\", style=filled, fillcolor=gold];
+    1 -> 3 [tailport=se];
+    3 [label=\"#3: 7\", style=filled, fillcolor=gold];
+    2 [label=\"#2: 22\", style=filled, fillcolor=gold];
+    2 -> 4 [tailport=sw];
+    4 [label=\"#4: 20\", style=filled, fillcolor=gold];
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_to_dot_escapes_keys() {
+        let tree = Tree::with_root("say \"hi\"");
+        assert!(tree
+            .to_dot(&[])
+            .contains("0 [label=\"#0: say \\\"hi\\\"\"];"));
+    }
+
+    #[test]
+    fn test_to_dot_bst_violation() {
+        let mut tree = sample();
+        tree.add_node(3, 1, false); // id 5
+        let violation = tree.bst_violation(Default::default()).unwrap();
+
+        let dot = tree.to_dot(&[violation.id]);
+        assert!(dot.contains("5 [label=\"#5: 1\", style=filled, fillcolor=gold];"));
+        assert!(dot.contains("3 [label=\"#3: 7\"];"));
+    }
+
+    #[test]
+    fn test_to_ascii() {
+        assert_eq!(
+            sample().to_ascii(),
+            "10 #0
+|-- L: 5 #1
+|   `-- R: 7 #3
+`-- R: 22 #2
+    `-- L: 20 #4
+"
+        );
+        assert_eq!(Tree::<u32>::new().to_ascii(), "");
+    }
+}
@@ -0,0 +1,163 @@
+use crate::Tree;
+
+impl<K> Tree<K> {
+    /// Solves a tree DP for every node as if it were the root, treating the tree
+    /// as an undirected graph, and returns the answer for each id, or `None` for
+    /// ids that are not in the tree. It runs in linear time.
+    ///
+    /// The value of a subtree is `lift(acc, key)`, where `key` is the key of its
+    /// root and `acc` merges, starting from `identity`, the values of the subtrees
+    /// hanging from its other neighbours. `merge` must be associative and commutative.
+    /// The answer for a node is the value of the whole tree rooted at it.
+    ///
+    /// For example, the sum of the distances from each node to all the others is
+    /// the second element of the answer with `identity` `(0, 0)`,
+    /// `merge` `|(n1, d1), (n2, d2)| (n1 + n2, d1 + d2)` and
+    /// `lift` `|(n, d), _| (n + 1, d + n)`, which counts the nodes and sums
+    /// their distances to the root of each subtree.
+    pub fn reroot<A, M, L>(&self, identity: A, merge: M, lift: L) -> Vec<Option<A>>
+    where
+        A: Clone,
+        M: Fn(A, A) -> A,
+        L: Fn(A, &K) -> A,
+    {
+        let mut answers = vec![None; self.nodes.len()];
+        let Some(root) = self.root else {
+            return answers;
+        };
+
+        // Parents come before their children
+        let mut order = Vec::with_capacity(self.len());
+        let mut stack = vec![root];
+        while let Some(id) = stack.pop() {
+            order.push(id);
+            let node = &self.nodes[id];
+            stack.extend(node.id_left.into_iter().chain(node.id_right));
+        }
+
+        // The value of the subtree below each node, rooted at it
+        let mut down: Vec<Option<A>> = vec![None; self.nodes.len()];
+        for &id in order.iter().rev() {
+            let node = &self.nodes[id];
+            let acc = [node.id_left, node.id_right]
+                .into_iter()
+                .flatten()
+                .fold(identity.clone(), |acc, child| {
+                    merge(acc, down[child].clone().unwrap())
+                });
+            down[id] = Some(lift(acc, node.key()));
+        }
+
+        // The value of the rest of the tree above each node, rooted at its parent
+        let mut up: Vec<Option<A>> = vec![None; self.nodes.len()];
+        for &id in &order {
+            let node = &self.nodes[id];
+            let children = [node.id_left, node.id_right];
+
+            for (i, child) in children.into_iter().enumerate() {
+                let Some(child) = child else {
+                    continue;
+                };
+                let mut acc = identity.clone();
+                if let Some(above) = up[id].clone() {
+                    acc = merge(acc, above);
+                }
+                if let Some(sibling) = children[1 - i] {
+                    acc = merge(acc, down[sibling].clone().unwrap());
+                }
+                up[child] = Some(lift(acc, node.key()));
+            }
+
+            let acc = up[id]
+                .clone()
+                .into_iter()
+                .chain(
+                    children
+                        .into_iter()
+                        .flatten()
+                        .map(|child| down[child].clone().unwrap()),
+                )
+                .fold(identity.clone(), &merge);
+            answers[id] = Some(lift(acc, node.key()));
+        }
+
+        answers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LcaIndex;
+
+    fn sample() -> Tree<u32> {
+        "[1, 2, 3, 4, 5, null, 6, null, null, 7, null, 8]"
+            .parse()
+            .unwrap()
+    }
+
+    fn ids<K>(tree: &Tree<K>) -> Vec<usize> {
+        (0..tree.nodes.len())
+            .filter(|&id| tree.contains_id(id))
+            .collect()
+    }
+
+    #[test]
+    fn test_reroot_sum_of_distances() {
+        let mut tree = sample();
+        tree.remove_subtree(6);
+        tree.add_node(3, 9, true); // reuses id 6
+
+        let answers = tree.reroot(
+            (0, 0),
+            |(n1, d1), (n2, d2)| (n1 + n2, d1 + d2),
+            |(n, d), _| (n + 1, d + n),
+        );
+
+        let index = LcaIndex::new(&tree);
+        let ids = ids(&tree);
+        for &a in &ids {
+            let expected: usize = ids.iter().map(|&b| index.distance(a, b).unwrap()).sum();
+            assert_eq!(answers[a], Some((tree.len(), expected)));
+        }
+    }
+
+    #[test]
+    fn test_reroot_eccentricity() {
+        let tree = sample();
+        // The height in edges of each subtree, `None` for no nodes
+        let answers = tree.reroot(
+            None,
+            |a: Option<usize>, b| a.max(b),
+            |height, _| Some(height.map_or(0, |height| height + 1)),
+        );
+
+        let index = LcaIndex::new(&tree);
+        let ids = ids(&tree);
+        for &a in &ids {
+            let expected = ids.iter().map(|&b| index.distance(a, b).unwrap()).max();
+            assert_eq!(answers[a], Some(expected));
+        }
+        // The farthest node from the leaf 8 is the leaf 7, six edges away
+        assert_eq!(answers[7], Some(Some(6)));
+    }
+
+    #[test]
+    fn test_reroot_uses_keys() {
+        // The largest key sum over the paths starting at each node
+        let tree = sample();
+        let answers = tree.reroot(0, u32::max, |best, &key| best + key);
+
+        assert_eq!(answers[0], Some(1 + 3 + 6 + 8));
+        assert_eq!(answers[6], Some(7 + 5 + 2 + 1 + 3 + 6 + 8));
+    }
+
+    #[test]
+    fn test_reroot_small_trees() {
+        let answers = Tree::<u32>::new().reroot(0, |a, b| a + b, |acc, _| acc + 1);
+        assert!(answers.is_empty());
+
+        let answers = Tree::with_root(5).reroot(0, |a, b| a + b, |acc, _| acc + 1);
+        assert_eq!(answers, [Some(1)]);
+    }
+}
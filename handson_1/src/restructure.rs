@@ -0,0 +1,213 @@
+use crate::{Tree, TreeError};
+
+impl<K> Tree<K> {
+    /// Swaps the left and right children of every node, turning the tree into
+    /// its mirror image. Node ids do not change.
+    pub fn mirror(&mut self) {
+        for node in self.nodes.iter_mut().filter(|node| node.key.is_some()) {
+            std::mem::swap(&mut node.id_left, &mut node.id_right);
+        }
+    }
+
+    /// Swaps the left and right children of the node `id`. Node ids do not change.
+    ///
+    /// # Panics
+    /// Panics if the node `id` does not exist.
+    pub fn swap_children(&mut self, id: usize) {
+        assert!(self.contains_id(id), "Node id does not exist");
+        let node = &mut self.nodes[id];
+        std::mem::swap(&mut node.id_left, &mut node.id_right);
+    }
+
+    /// Rotates the subtree rooted at `id` to the left: the right child of `id`
+    /// takes its place and `id` becomes its left child. Returns the id of the
+    /// new root of the subtree, or `None` without changes if `id` has no right child.
+    /// Node ids do not change, and the in-order sequence of keys is preserved.
+    ///
+    /// # Panics
+    /// Panics if the node `id` does not exist.
+    pub fn rotate_left(&mut self, id: usize) -> Option<usize> {
+        assert!(self.contains_id(id), "Node id does not exist");
+
+        let pivot = self.nodes[id].id_right?;
+        let inner = self.nodes[pivot].id_left;
+        let parent_id = self.nodes[id].id_parent;
+
+        self.set_child(id, false, inner);
+        self.replace_link(parent_id, id, Some(pivot));
+        self.set_child(pivot, true, Some(id));
+        Some(pivot)
+    }
+
+    /// Rotates the subtree rooted at `id` to the right: the left child of `id`
+    /// takes its place and `id` becomes its right child. Returns the id of the
+    /// new root of the subtree, or `None` without changes if `id` has no left child.
+    /// Node ids do not change, and the in-order sequence of keys is preserved.
+    ///
+    /// # Panics
+    /// Panics if the node `id` does not exist.
+    pub fn rotate_right(&mut self, id: usize) -> Option<usize> {
+        assert!(self.contains_id(id), "Node id does not exist");
+
+        let pivot = self.nodes[id].id_left?;
+        let inner = self.nodes[pivot].id_right;
+        let parent_id = self.nodes[id].id_parent;
+
+        self.set_child(id, true, inner);
+        self.replace_link(parent_id, id, Some(pivot));
+        self.set_child(pivot, false, Some(id));
+        Some(pivot)
+    }
+
+    /// Moves all the nodes of `other` into this tree, hanging its root as the
+    /// left child of `parent_id` iff `is_left` is `true`, the right child otherwise.
+    /// Returns a table mapping every id of `other` to its new id in this tree,
+    /// with `None` for the ids that were not in `other`.
+    pub fn graft(
+        &mut self,
+        parent_id: usize,
+        other: Tree<K>,
+        is_left: bool,
+    ) -> Result<Vec<Option<usize>>, TreeError> {
+        self.check_free_slot(parent_id, is_left)?;
+
+        let Tree {
+            mut nodes, root, ..
+        } = other;
+        let mut new_ids = vec![None; nodes.len()];
+        let mut stack = Vec::from_iter(root.map(|root| (root, parent_id, is_left)));
+
+        while let Some((id, parent_id, is_left)) = stack.pop() {
+            let node = &mut nodes[id];
+            let key = node.key.take().expect("Node id does not exist");
+            let new_id = self.link_new_child(parent_id, key, is_left);
+            new_ids[id] = Some(new_id);
+
+            stack.extend(node.id_right.map(|right| (right, new_id, false)));
+            stack.extend(node.id_left.map(|left| (left, new_id, true)));
+        }
+
+        Ok(new_ids)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Tree<u32> {
+        s.parse().unwrap()
+    }
+
+    fn in_order(tree: &Tree<u32>) -> Vec<u32> {
+        let mut keys = Vec::new();
+        let mut stack = Vec::new();
+        let mut current = tree.root;
+        while current.is_some() || !stack.is_empty() {
+            while let Some(id) = current {
+                stack.push(id);
+                current = tree.nodes[id].id_left;
+            }
+            let id = stack.pop().unwrap();
+            keys.push(*tree.nodes[id].key());
+            current = tree.nodes[id].id_right;
+        }
+        keys
+    }
+
+    #[test]
+    fn test_mirror() {
+        let mut tree = parse("[1, 2, 3, 4, null, null, 5]");
+        tree.mirror();
+        assert_eq!(
+            tree.to_level_order(),
+            [Some(1), Some(3), Some(2), Some(5), None, None, Some(4)]
+        );
+        assert_eq!(tree.cursor(3).unwrap().parent().unwrap().id(), 1);
+
+        tree.mirror();
+        assert!(tree.is_identical(&parse("[1, 2, 3, 4, null, null, 5]")));
+    }
+
+    #[test]
+    fn test_swap_children() {
+        let mut tree = parse("[1, 2, 3, 4]");
+        tree.swap_children(1);
+        assert_eq!(
+            tree.to_level_order(),
+            [Some(1), Some(2), Some(3), None, Some(4)]
+        );
+    }
+
+    #[test]
+    fn test_rotations() {
+        let mut tree = parse("[10, 5, 20, 3, 7, 15, 30]");
+        let keys = in_order(&tree);
+
+        assert_eq!(tree.rotate_left(0), Some(2));
+        assert_eq!(tree.root(), Some(2));
+        assert_eq!(
+            tree.to_level_order(),
+            [
+                Some(20),
+                Some(10),
+                Some(30),
+                Some(5),
+                Some(15),
+                None,
+                None,
+                Some(3),
+                Some(7)
+            ]
+        );
+        assert_eq!(in_order(&tree), keys);
+
+        // Rotating an inner node relinks it to its parent
+        assert_eq!(tree.rotate_right(0), Some(1));
+        assert_eq!(tree.nodes[2].id_left, Some(1));
+        assert_eq!(tree.cursor(1).unwrap().parent().unwrap().id(), 2);
+        assert_eq!(in_order(&tree), keys);
+        assert!(tree.is_bst());
+
+        assert_eq!(tree.rotate_right(5), None);
+        assert_eq!(tree.rotate_left(6), None);
+    }
+
+    #[test]
+    fn test_graft() {
+        let mut tree = parse("[1, 2, 3]");
+        let mut other = parse("[7, 8, 9]");
+        other.remove_subtree(1);
+        other.add_node(2, 10, true); // reuses id 1
+
+        let new_ids = tree.graft(2, other, false).unwrap();
+        assert_eq!(new_ids, [Some(3), Some(5), Some(4)]);
+        assert_eq!(
+            tree.to_level_order(),
+            [
+                Some(1),
+                Some(2),
+                Some(3),
+                None,
+                None,
+                None,
+                Some(7),
+                None,
+                Some(9),
+                Some(10)
+            ]
+        );
+        assert_eq!(tree.cursor(3).unwrap().parent().unwrap().id(), 2);
+        assert_eq!(tree.len(), 6);
+
+        assert_eq!(
+            tree.graft(2, Tree::with_root(5), false),
+            Err(TreeError::RightOccupied(2))
+        );
+        assert_eq!(
+            tree.graft(9, Tree::with_root(5), true),
+            Err(TreeError::UnknownParent(9))
+        );
+        assert_eq!(tree.graft(1, Tree::new(), true), Ok(vec![]));
+    }
+}
@@ -0,0 +1,239 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use crate::Tree;
+
+/// Whether comparisons of subtrees tell left children from right children.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Orientation {
+    /// Two subtrees match only if their left and right children match in the same order.
+    Ordered,
+    /// Two subtrees also match when the children of any node are swapped.
+    Unordered,
+}
+
+/// Assigns the same class to subtrees with the same shape and keys, possibly
+/// across several trees. Unlike hashes, classes never collide.
+struct Classifier<'a, K> {
+    orientation: Orientation,
+    classes: HashMap<(&'a K, Option<usize>, Option<usize>), usize>,
+}
+
+impl<'a, K: Hash + Eq> Classifier<'a, K> {
+    fn new(orientation: Orientation) -> Self {
+        Self {
+            orientation,
+            classes: HashMap::new(),
+        }
+    }
+
+    /// Returns the class of the subtree rooted at each id of `tree`, or `None` for free slots.
+    fn classify(&mut self, tree: &'a Tree<K>) -> Vec<Option<usize>> {
+        let mut classes = vec![None; tree.nodes.len()];
+
+        for id in tree.bottom_up() {
+            let node = &tree.nodes[id];
+            let mut children = (
+                node.id_left.and_then(|left| classes[left]),
+                node.id_right.and_then(|right| classes[right]),
+            );
+            if self.orientation == Orientation::Unordered && children.0 > children.1 {
+                children = (children.1, children.0);
+            }
+
+            let next_class = self.classes.len();
+            classes[id] = Some(
+                *self
+                    .classes
+                    .entry((node.key(), children.0, children.1))
+                    .or_insert(next_class),
+            );
+        }
+
+        classes
+    }
+}
+
+impl<K> Tree<K> {
+    /// A private function that returns the ids of the nodes so that
+    /// every node comes after its children.
+    fn bottom_up(&self) -> Vec<usize> {
+        let mut order = Vec::with_capacity(self.len());
+        let mut stack = Vec::from_iter(self.root);
+
+        while let Some(id) = stack.pop() {
+            order.push(id);
+            let node = &self.nodes[id];
+            stack.extend(node.id_left.into_iter().chain(node.id_right));
+        }
+
+        order.reverse();
+        order
+    }
+}
+
+impl<K: Hash + Eq> Tree<K> {
+    /// Returns a hash of the subtree rooted at each id, or `None` for ids that
+    /// are not in the tree. The hash depends only on the keys and the shape of the
+    /// subtree, so equal subtrees of different trees get equal hashes, and with
+    /// [`Orientation::Unordered`] swapping the children of a node does not change it.
+    ///
+    /// The hashes come from [`DefaultHasher`], whose algorithm may change between
+    /// Rust releases, so they are only comparable within one build of the program
+    /// and should not be stored or sent elsewhere.
+    pub fn subtree_hashes(&self, orientation: Orientation) -> Vec<Option<u64>> {
+        let mut hashes = vec![None; self.nodes.len()];
+
+        for id in self.bottom_up() {
+            let node = &self.nodes[id];
+            let mut children = (
+                node.id_left.and_then(|left| hashes[left]),
+                node.id_right.and_then(|right| hashes[right]),
+            );
+            if orientation == Orientation::Unordered && children.0 > children.1 {
+                children = (children.1, children.0);
+            }
+
+            let mut hasher = DefaultHasher::new();
+            (node.key(), children).hash(&mut hasher);
+            hashes[id] = Some(hasher.finish());
+        }
+
+        hashes
+    }
+
+    /// Returns the groups of two or more nodes whose subtrees have the same shape
+    /// and keys, as allowed by `orientation`. Groups are sorted by their first id,
+    /// and the ids in each group are sorted.
+    pub fn duplicate_subtrees(&self, orientation: Orientation) -> Vec<Vec<usize>> {
+        let classes = Classifier::new(orientation).classify(self);
+
+        let mut groups: HashMap<usize, Vec<usize>> = HashMap::new();
+        for (id, class) in classes.into_iter().enumerate() {
+            if let Some(class) = class {
+                groups.entry(class).or_default().push(id);
+            }
+        }
+
+        let mut groups: Vec<Vec<usize>> = groups
+            .into_values()
+            .filter(|group| group.len() > 1)
+            .collect();
+        groups.sort();
+        groups
+    }
+
+    /// Returns `true` if the two trees have the same shape and the same keys
+    /// in the same places. Node ids do not matter.
+    pub fn is_identical(&self, other: &Tree<K>) -> bool {
+        self.same_class(other, Orientation::Ordered)
+    }
+
+    /// Returns `true` if the two trees become identical after swapping
+    /// the children of some of their nodes.
+    pub fn is_isomorphic(&self, other: &Tree<K>) -> bool {
+        self.same_class(other, Orientation::Unordered)
+    }
+
+    /// A private function that classifies both trees with the same classifier
+    /// and compares the classes of their roots.
+    fn same_class(&self, other: &Tree<K>, orientation: Orientation) -> bool {
+        if self.len() != other.len() {
+            return false;
+        }
+
+        let mut classifier = Classifier::new(orientation);
+        let classes = classifier.classify(self);
+        let other_classes = classifier.classify(other);
+
+        let root_class = self.root.and_then(|root| classes[root]);
+        let other_root_class = other.root.and_then(|root| other_classes[root]);
+        root_class == other_root_class
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Tree<u32> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_duplicate_subtrees() {
+        // Two copies of 2 -> 4 and three copies of the leaf 4
+        let tree = parse("[1, 2, 3, 4, null, 2, 4, null, null, 4]");
+
+        assert_eq!(
+            tree.duplicate_subtrees(Orientation::Ordered),
+            vec![vec![1, 4], vec![3, 5, 6]]
+        );
+    }
+
+    #[test]
+    fn test_duplicate_subtrees_unordered() {
+        let tree = parse("[1, 2, 2, 3, null, null, 3]");
+
+        assert_eq!(
+            tree.duplicate_subtrees(Orientation::Ordered),
+            vec![vec![3, 4]]
+        );
+        assert_eq!(
+            tree.duplicate_subtrees(Orientation::Unordered),
+            vec![vec![1, 2], vec![3, 4]]
+        );
+    }
+
+    #[test]
+    fn test_subtree_hashes() {
+        let tree = parse("[1, 2, 2, 3, null, null, 3]");
+
+        let ordered = tree.subtree_hashes(Orientation::Ordered);
+        assert_eq!(ordered[3], ordered[4]);
+        assert_ne!(ordered[1], ordered[2]);
+
+        let unordered = tree.subtree_hashes(Orientation::Unordered);
+        assert_eq!(unordered[1], unordered[2]);
+
+        // Equal subtrees of different trees hash the same
+        let other = parse("[2, 3]");
+        assert_eq!(other.subtree_hashes(Orientation::Ordered)[0], ordered[1]);
+    }
+
+    #[test]
+    fn test_is_identical() {
+        let tree = parse("[1, 2, 3, null, 4]");
+        let mut other = Tree::with_root(1);
+        other.add_node(0, 3, false);
+        let two = other.add_node(0, 2, true);
+        other.add_node(two, 4, false);
+
+        assert!(tree.is_identical(&other));
+        assert!(!tree.is_identical(&parse("[1, 2, 3, 4]")));
+        assert!(!tree.is_identical(&parse("[1, 2, 3, null, 5]")));
+        assert!(Tree::<u32>::new().is_identical(&Tree::new()));
+    }
+
+    #[test]
+    fn test_is_isomorphic() {
+        let tree = parse("[1, 2, 3, null, 4]");
+
+        assert!(tree.is_isomorphic(&parse("[1, 3, 2, null, null, 4]")));
+        assert!(!tree.is_identical(&parse("[1, 3, 2, null, null, 4]")));
+        assert!(!tree.is_isomorphic(&parse("[1, 3, 2, 4]")));
+    }
+
+    #[test]
+    fn test_classifier_shares_classes() {
+        let mut classifier = Classifier::new(Orientation::Ordered);
+        let tree = parse("[1, 2, 3]");
+        let other = parse("[5, 2, 3]");
+        classifier.classify(&tree);
+        classifier.classify(&other);
+
+        // The leaves 2 and 3 are shared, the roots are not
+        assert_eq!(classifier.classes.len(), 4);
+    }
+}